@@ -104,6 +104,16 @@ async function run() {
 	// Create the renderer
 	const renderer = new wasm.Renderer(canvas, scene, settings);
 
+	// Report the progress of the render
+	renderer.set_progress_callbacks(
+		(progress: wasm.Progress) => {
+			document.title = `Photon ${(progress.fraction * 100).toFixed(1)}%`;
+		},
+		(progress: wasm.Progress) => {
+			console.log(`Finished rendering with ${progress.samples_per_pixel} samples per pixel`);
+		}
+	);
+
 	// Render the scene
 	renderer.run();
 }
@@ -0,0 +1,91 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{init_panic_hook, vector::Vector};
+
+// Rust Aabb struct, an axis-aligned bounding box
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+#[wasm_bindgen]
+impl Aabb {
+    #[wasm_bindgen(constructor)]
+    pub fn new(min: Vector, max: Vector) -> Aabb {
+        init_panic_hook();
+
+        Aabb { min, max }
+    }
+
+    // Box that contains all of space, used for objects like infinite planes
+    pub fn infinite() -> Aabb {
+        Aabb {
+            min: Vector::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            max: Vector::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        }
+    }
+
+    // Box around a point with the given half size along each axis
+    pub fn from_center(center: Vector, half_size: Vector) -> Aabb {
+        Aabb {
+            min: center - half_size,
+            max: center + half_size,
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Vector::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Vector::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn include_point(&self, point: &Vector) -> Aabb {
+        self.union(&Aabb {
+            min: *point,
+            max: *point,
+        })
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|v| v.x.is_finite() && v.y.is_finite() && v.z.is_finite())
+    }
+
+    pub fn center(&self) -> Vector {
+        (self.min + self.max) / 2.0
+    }
+}
+
+impl Aabb {
+    // Method to get the eight corners of the box
+    pub fn corners(&self) -> [Vector; 8] {
+        let mut corners = [Vector::default(); 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            corner.x = if i & 1 == 0 { self.min.x } else { self.max.x };
+            corner.y = if i & 2 == 0 { self.min.y } else { self.max.y };
+            corner.z = if i & 4 == 0 { self.min.z } else { self.max.z };
+        }
+
+        corners
+    }
+}
+
+// Half size along each axis of a disk with the given normal and radius
+pub fn disk_extent(normal: &Vector, radius: f64) -> Vector {
+    let normal = normal.normalize();
+    let extent = |n: f64| radius * (1.0 - n * n).max(0.0).sqrt();
+
+    Vector::new(extent(normal.x), extent(normal.y), extent(normal.z))
+}
@@ -0,0 +1,650 @@
+use std::f64::consts::PI;
+
+use crate::{
+    conductor::Conductor, intersection::project_on_surface, material::Material, random::Random,
+    thin_film::ThinFilm, vector::Vector,
+};
+
+// Smallest roughness of the microfacet lobes, smoother surfaces are mirrors in practice
+const MIN_ALPHA: f64 = 1e-3;
+
+// Rust BsdfSample struct, a direction picked by the bsdf
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct BsdfSample {
+    pub direction: Vector,
+    pub weight: Vector, // How much of the light from the direction is scattered, divided by the probability
+}
+
+pub(crate) fn luminance(color: &Vector) -> f64 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
+fn lerp(a: Vector, b: Vector, t: f64) -> Vector {
+    a * (1.0 - t) + b * t
+}
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+fn schlick(f0: Vector, cos_theta: f64) -> Vector {
+    lerp(f0, Vector::new(1.0, 1.0, 1.0), schlick_weight(cos_theta))
+}
+
+// Fresnel reflectance of a dielectric, eta is the index of the side the light goes into over the side it comes from
+pub(crate) fn fresnel_dielectric(cos_theta_i: f64, eta: f64) -> f64 {
+    let (cos_theta_i, eta) = if cos_theta_i < 0.0 {
+        (-cos_theta_i, 1.0 / eta)
+    } else {
+        (cos_theta_i.min(1.0), eta)
+    };
+
+    let sin2_theta_t = (1.0 - cos_theta_i * cos_theta_i) / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        // Total internal reflection
+        return 1.0;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+    (r_parallel * r_parallel + r_perpendicular * r_perpendicular) / 2.0
+}
+
+// Method to bend a direction that leaves the surface on the side of the normal through it, None on total internal reflection
+fn refract(outgoing: &Vector, normal: &Vector, eta: f64) -> Option<Vector> {
+    let cos_theta_i = normal.dot(outgoing);
+    let sin2_theta_t = (1.0 - cos_theta_i * cos_theta_i).max(0.0) / (eta * eta);
+    if sin2_theta_t >= 1.0 {
+        return None;
+    }
+    let cos_theta_t = (1.0 - sin2_theta_t).sqrt();
+
+    Some(*outgoing * (-1.0 / eta) + *normal * (cos_theta_i / eta - cos_theta_t))
+}
+
+// Rust Microfacet struct, an anisotropic GGX distribution in the frame where the normal is z
+#[derive(Debug, Copy, Clone)]
+struct Microfacet {
+    alpha_x: f64,
+    alpha_y: f64,
+}
+
+impl Microfacet {
+    fn new(alpha_x: f64, alpha_y: f64) -> Microfacet {
+        Microfacet {
+            alpha_x: alpha_x.max(MIN_ALPHA),
+            alpha_y: alpha_y.max(MIN_ALPHA),
+        }
+    }
+
+    // Density of the microfacets facing the half vector
+    fn distribution(&self, half: &Vector) -> f64 {
+        let x = half.x / self.alpha_x;
+        let y = half.y / self.alpha_y;
+        let d = x * x + y * y + half.z * half.z;
+
+        1.0 / (PI * self.alpha_x * self.alpha_y * d * d)
+    }
+
+    fn lambda(&self, w: &Vector) -> f64 {
+        if w.z == 0.0 {
+            return f64::INFINITY;
+        }
+        let x = self.alpha_x * w.x;
+        let y = self.alpha_y * w.y;
+
+        ((1.0 + (x * x + y * y) / (w.z * w.z)).sqrt() - 1.0) / 2.0
+    }
+
+    // Fraction of the microfacets that can be seen from the direction
+    fn masking(&self, w: &Vector) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    // Fraction of the microfacets that can be seen from both directions
+    fn shadowing(&self, outgoing: &Vector, incoming: &Vector) -> f64 {
+        1.0 / (1.0 + self.lambda(outgoing) + self.lambda(incoming))
+    }
+
+    // Method to pick a half vector among the microfacets seen from the direction, by Heitz
+    fn sample_visible(&self, outgoing: &Vector, u1: f64, u2: f64) -> Vector {
+        // Stretch the view so the distribution is a hemisphere
+        let view = Vector::new(
+            self.alpha_x * outgoing.x,
+            self.alpha_y * outgoing.y,
+            outgoing.z,
+        )
+        .normalize();
+
+        let length_squared = view.x * view.x + view.y * view.y;
+        let t1 = if length_squared > 0.0 {
+            Vector::new(-view.y, view.x, 0.0) / length_squared.sqrt()
+        } else {
+            Vector::new(1.0, 0.0, 0.0)
+        };
+        let t2 = view.cross(&t1);
+
+        // Point on the projected hemisphere, squeezed by how much of it can be seen
+        let r = u1.sqrt();
+        let phi = 2.0 * PI * u2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + view.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * r * phi.sin();
+        let half = t1 * p1 + t2 * p2 + view * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+
+        // Unstretch
+        Vector::new(
+            self.alpha_x * half.x,
+            self.alpha_y * half.y,
+            half.z.max(0.0),
+        )
+        .normalize()
+    }
+
+    // Probability density of sample_visible giving the half vector
+    fn visible_pdf(&self, outgoing: &Vector, half: &Vector) -> f64 {
+        if outgoing.z <= 0.0 {
+            return 0.0;
+        }
+
+        self.masking(outgoing) * outgoing.dot(half).max(0.0) * self.distribution(half) / outgoing.z
+    }
+}
+
+// Rust Bsdf struct, the principled material at a hit as diffuse, sheen, specular, clearcoat and transmission lobes
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Bsdf {
+    tangent: Vector, // Frame of the shading normal
+    bitangent: Vector,
+    normal: Vector,
+    base_color: Vector,
+    roughness: f64,
+    sheen: f64,
+    clearcoat: f64,
+    specular_color: Vector, // Reflectance of the specular lobe when seen head-on
+    dielectric_color: Vector,
+    metallic: f64,
+    conductor: Option<Conductor>, // Measured metal that replaces the base color in the reflections
+    thin_film: Option<ThinFilm>,  // Film on top of the specular and glass reflections
+    eta: f64, // Index of refraction inside over outside, flipped when the ray is inside
+    coat_eta: f64,
+    coat_color: Vector, // Color of the coat seen head-on
+    specular: Microfacet,
+    coat: Microfacet,
+    diffuse_weight: f64,
+    specular_weight: f64,
+    transmission_weight: f64,
+    transmission_color: Vector, // Tint of the light refracted through the surface
+    probabilities: [f64; 4], // Of picking the diffuse, specular, clearcoat and transmission lobes
+}
+
+impl Bsdf {
+    // The normal faces against the ray and dpdu gives the direction the anisotropy follows
+    pub fn new(material: &Material, normal: &Vector, dpdu: &Vector, front_face: bool) -> Bsdf {
+        let tangent = project_on_surface(dpdu, normal);
+        let tangent = if tangent.magnitude() > 1e-12 {
+            tangent.normalize()
+        } else {
+            normal.orthonormal_basis().0
+        };
+        let bitangent = normal.cross(&tangent);
+
+        let base_color = material.color;
+        let metallic = material.metallic.clamp(0.0, 1.0);
+        let transmission = material.transmission.clamp(0.0, 1.0);
+        let roughness = material.roughness.clamp(0.0, 1.0);
+
+        // Dielectric reflectance from the specular amount, optionally tinted by the hue of the base color
+        let base_luminance = luminance(&base_color);
+        let tint = if base_luminance > 0.0 {
+            base_color / base_luminance
+        } else {
+            Vector::new(1.0, 1.0, 1.0)
+        };
+        let dielectric_color = lerp(
+            Vector::new(1.0, 1.0, 1.0),
+            tint,
+            material.specular_tint.clamp(0.0, 1.0),
+        ) * (0.08 * material.specular.max(0.0));
+        let metal_color = material
+            .conductor
+            .map_or(base_color, |conductor| conductor.fresnel(1.0));
+        let specular_color = lerp(dielectric_color, metal_color, metallic);
+
+        let alpha = roughness * roughness;
+        let aspect = (1.0 - 0.9 * material.anisotropy.clamp(0.0, 1.0)).sqrt();
+        let coat_roughness = material.clearcoat_roughness.clamp(0.0, 1.0);
+        let coat_alpha = coat_roughness * coat_roughness;
+
+        // Subsurface scattering refracts into the object like glass, the medium inside gives it its color
+        let subsurface = (1.0 - transmission) * material.subsurface.clamp(0.0, 1.0);
+        let diffuse_weight = (1.0 - metallic) * (1.0 - transmission - subsurface);
+        let transmission_weight = (1.0 - metallic) * (transmission + subsurface);
+        let specular_weight = 1.0 - transmission_weight;
+        let transmission_color = if transmission + subsurface > 0.0 {
+            (base_color * transmission + Vector::new(1.0, 1.0, 1.0) * subsurface)
+                / (transmission + subsurface)
+        } else {
+            base_color
+        };
+        let clearcoat = material.clearcoat.clamp(0.0, 1.0);
+
+        // Pick the lobes roughly by how much light they scatter
+        let probabilities = [
+            diffuse_weight * base_luminance.max(0.0),
+            specular_weight * (0.5 + 0.5 * luminance(&specular_color).clamp(0.0, 1.0)),
+            0.5 * clearcoat,
+            transmission_weight,
+        ];
+        let total: f64 = probabilities.iter().sum();
+        let probabilities = if total > 0.0 {
+            probabilities.map(|p| p / total)
+        } else {
+            [0.0; 4]
+        };
+
+        // Refraction has no half vector without a change in the index, so it is kept just above 1
+        let ior = material.ior.max(1.001);
+        let outside = if front_face { 1.0 } else { ior };
+        let thin_film = (material.thin_film_thickness > 0.0).then(|| ThinFilm {
+            thickness: material.thin_film_thickness,
+            eta: material.thin_film_ior.max(1.0) / outside,
+            wavelengths: material.wavelengths,
+        });
+
+        Bsdf {
+            tangent,
+            bitangent,
+            normal: *normal,
+            base_color,
+            roughness,
+            sheen: material.sheen.max(0.0),
+            clearcoat,
+            specular_color,
+            dielectric_color,
+            metallic,
+            conductor: material.conductor,
+            thin_film,
+            eta: if front_face { ior } else { 1.0 / ior },
+            coat_eta: material.clearcoat_ior.max(1.0),
+            coat_color: material.clearcoat_color,
+            specular: Microfacet::new(alpha / aspect, alpha * aspect),
+            coat: Microfacet::new(coat_alpha, coat_alpha),
+            diffuse_weight,
+            specular_weight,
+            transmission_weight,
+            transmission_color,
+            probabilities,
+        }
+    }
+
+    // Method to get the reflectance of the specular lobe, the metallic part follows the measured metal if there is one
+    fn specular_fresnel(&self, cos_theta: f64) -> Vector {
+        let Some(film) = self.thin_film else {
+            return match self.conductor {
+                Some(conductor) => lerp(
+                    schlick(self.dielectric_color, cos_theta),
+                    conductor.fresnel(cos_theta),
+                    self.metallic,
+                ),
+                None => schlick(self.specular_color, cos_theta),
+            };
+        };
+
+        // The film sits on a dielectric with the index of refraction of the material, or on the metal
+        let dielectric = film.reflectance(
+            cos_theta,
+            &Vector::new(self.eta, self.eta, self.eta),
+            &Vector::default(),
+        );
+        if self.metallic <= 0.0 {
+            return dielectric;
+        }
+        let metal = match self.conductor {
+            Some(conductor) => film.reflectance(cos_theta, &conductor.eta, &conductor.k),
+            None => {
+                // Real index of refraction that reflects the base color head-on
+                let eta = |f0: f64| {
+                    let r = f0.clamp(0.0, 0.98).sqrt();
+                    (1.0 + r) / (1.0 - r)
+                };
+                let base = Vector::new(
+                    eta(self.base_color.x),
+                    eta(self.base_color.y),
+                    eta(self.base_color.z),
+                );
+                film.reflectance(cos_theta, &base, &Vector::default())
+            }
+        };
+
+        lerp(dielectric, metal, self.metallic)
+    }
+
+    // Method to get the reflectance of the glass and the probability of picking the reflection over the refraction
+    fn glass_fresnel(&self, cos_theta: f64) -> (Vector, f64) {
+        match self.thin_film {
+            Some(film) => {
+                let reflectance = film.reflectance(
+                    cos_theta,
+                    &Vector::new(self.eta, self.eta, self.eta),
+                    &Vector::default(),
+                );
+                let average = (reflectance.x + reflectance.y + reflectance.z) / 3.0;
+
+                (reflectance, average)
+            }
+            None => {
+                let fresnel = fresnel_dielectric(cos_theta, self.eta);
+
+                (Vector::new(fresnel, fresnel, fresnel), fresnel)
+            }
+        }
+    }
+
+    // Method to get how much of the light gets through the clear coat to the layers below and back out
+    fn coat_transmittance(&self, cos_o: f64, cos_i: f64) -> Vector {
+        if self.clearcoat <= 0.0 {
+            return Vector::new(1.0, 1.0, 1.0);
+        }
+
+        // Light is partly reflected at the top of the coat and absorbed along its path through it
+        let refracted = |cos: f64| {
+            (1.0 - (1.0 - cos * cos) / (self.coat_eta * self.coat_eta))
+                .max(1e-4)
+                .sqrt()
+        };
+        let path = 0.5 / refracted(cos_o) + 0.5 / refracted(cos_i);
+        let passed = (1.0 - fresnel_dielectric(cos_o, self.coat_eta))
+            * (1.0 - fresnel_dielectric(cos_i, self.coat_eta));
+        let absorbed = Vector::new(
+            self.coat_color.x.max(0.0).powf(path),
+            self.coat_color.y.max(0.0).powf(path),
+            self.coat_color.z.max(0.0).powf(path),
+        );
+
+        lerp(
+            Vector::new(1.0, 1.0, 1.0),
+            absorbed * passed,
+            self.clearcoat,
+        )
+    }
+
+    fn local(&self, v: &Vector) -> Vector {
+        Vector::new(
+            v.dot(&self.tangent),
+            v.dot(&self.bitangent),
+            v.dot(&self.normal),
+        )
+    }
+
+    fn world(&self, v: &Vector) -> Vector {
+        self.tangent * v.x + self.bitangent * v.y + self.normal * v.z
+    }
+
+    // Method to get the value of the bsdf and the probability density of sampling the incoming direction, in the local frame
+    fn evaluate_local(&self, outgoing: &Vector, incoming: &Vector) -> (Vector, f64) {
+        let mut value = Vector::default();
+        let mut pdf = 0.0;
+        let [diffuse_probability, specular_probability, clearcoat_probability, transmission_probability] =
+            self.probabilities;
+        let (cos_o, cos_i) = (outgoing.z, incoming.z);
+
+        if cos_o <= 0.0 || cos_i == 0.0 {
+            return (value, pdf);
+        }
+
+        let coat_transmittance = self.coat_transmittance(cos_o, cos_i.abs());
+
+        if cos_i > 0.0 {
+            let half = (*outgoing + *incoming).normalize();
+            let cos_d = incoming.dot(&half);
+            let cos_oh = outgoing.dot(&half);
+
+            // Diffuse with retro-reflection at grazing angles on rough surfaces, and sheen on top
+            if self.diffuse_weight > 0.0 {
+                let fd90 = 0.5 + 2.0 * self.roughness * cos_d * cos_d;
+                let fl = 1.0 + (fd90 - 1.0) * schlick_weight(cos_i);
+                let fv = 1.0 + (fd90 - 1.0) * schlick_weight(cos_o);
+                let diffuse = self.base_color * (fl * fv / PI);
+                let sheen = self.sheen * schlick_weight(cos_d);
+
+                value += (diffuse + Vector::new(sheen, sheen, sheen))
+                    * coat_transmittance
+                    * self.diffuse_weight;
+                pdf += diffuse_probability * cos_i / PI;
+            }
+
+            if self.specular_weight > 0.0 {
+                let specular = self.specular.distribution(&half)
+                    * self.specular.shadowing(outgoing, incoming)
+                    / (4.0 * cos_o * cos_i);
+
+                value += self.specular_fresnel(cos_d)
+                    * coat_transmittance
+                    * (specular * self.specular_weight);
+                pdf += specular_probability * self.specular.visible_pdf(outgoing, &half)
+                    / (4.0 * cos_oh);
+            }
+
+            if self.clearcoat > 0.0 {
+                let fresnel = fresnel_dielectric(cos_d, self.coat_eta);
+                let coat = self.coat.distribution(&half) * self.coat.shadowing(outgoing, incoming)
+                    / (4.0 * cos_o * cos_i);
+                let coat = self.clearcoat * fresnel * coat;
+
+                value += Vector::new(coat, coat, coat);
+                pdf +=
+                    clearcoat_probability * self.coat.visible_pdf(outgoing, &half) / (4.0 * cos_oh);
+            }
+
+            // Reflection off the glass, which is not tinted
+            if self.transmission_weight > 0.0 {
+                let (fresnel, reflect_probability) = self.glass_fresnel(cos_oh);
+                let reflection = self.specular.distribution(&half)
+                    * self.specular.shadowing(outgoing, incoming)
+                    / (4.0 * cos_o * cos_i);
+
+                value += fresnel * coat_transmittance * (self.transmission_weight * reflection);
+                pdf += transmission_probability
+                    * reflect_probability
+                    * self.specular.visible_pdf(outgoing, &half)
+                    / (4.0 * cos_oh);
+            }
+        } else if self.transmission_weight > 0.0 {
+            // Refraction through the glass, with the half vector of Walter et al.
+            let half = (*incoming * self.eta + *outgoing).normalize();
+            let half = if half.z < 0.0 { half * -1.0 } else { half };
+            let (cos_ih, cos_oh) = (incoming.dot(&half), outgoing.dot(&half));
+            if cos_ih >= 0.0 || cos_oh <= 0.0 {
+                return (value, pdf);
+            }
+
+            let denominator = (cos_ih + cos_oh / self.eta).powi(2);
+            if denominator <= 0.0 {
+                return (value, pdf);
+            }
+            let (fresnel, reflect_probability) = self.glass_fresnel(cos_oh);
+            let refraction = self.specular.distribution(&half)
+                * self.specular.shadowing(outgoing, incoming)
+                * (cos_ih * cos_oh / (cos_i * cos_o * denominator)).abs()
+                / (self.eta * self.eta);
+
+            value += (Vector::new(1.0, 1.0, 1.0) - fresnel)
+                * self.transmission_color
+                * coat_transmittance
+                * (self.transmission_weight * refraction);
+            pdf += transmission_probability
+                * (1.0 - reflect_probability)
+                * self.specular.visible_pdf(outgoing, &half)
+                * cos_ih.abs()
+                / denominator;
+        }
+
+        (value, pdf)
+    }
+
+    // Method to pick the direction the light comes from for a ray going in the direction
+    pub fn sample(&self, direction: &Vector, random: &mut Random) -> Option<BsdfSample> {
+        let outgoing = self.local(&(*direction * -1.0).normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let (u1, u2) = (random.random_value(), random.random_value());
+        let mut choice = random.random_value();
+        let lobe = self
+            .probabilities
+            .iter()
+            .position(|p| {
+                choice -= p;
+                choice < 0.0
+            })
+            .unwrap_or_else(|| {
+                self.probabilities
+                    .iter()
+                    .rposition(|p| *p > 0.0)
+                    .unwrap_or(0)
+            });
+        let reflect = |half: &Vector| (outgoing * -1.0).reflect(half);
+
+        let incoming = match lobe {
+            0 => {
+                // Cosine-weighted direction on the hemisphere
+                let r = u1.sqrt();
+                let phi = 2.0 * PI * u2;
+                Vector::new(r * phi.cos(), r * phi.sin(), (1.0 - u1).max(0.0).sqrt())
+            }
+            1 => reflect(&self.specular.sample_visible(&outgoing, u1, u2)),
+            2 => reflect(&self.coat.sample_visible(&outgoing, u1, u2)),
+            _ => {
+                let half = self.specular.sample_visible(&outgoing, u1, u2);
+                let (_, reflect_probability) = self.glass_fresnel(outgoing.dot(&half));
+                if random.random_value() < reflect_probability {
+                    reflect(&half)
+                } else {
+                    refract(&outgoing, &half, self.eta).unwrap_or_else(|| reflect(&half))
+                }
+            }
+        };
+
+        let (value, pdf) = self.evaluate_local(&outgoing, &incoming);
+        if pdf <= 0.0 || !pdf.is_finite() {
+            return None;
+        }
+
+        Some(BsdfSample {
+            direction: self.world(&incoming),
+            weight: value * (incoming.z.abs() / pdf),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Ray coming down at an angle onto a surface facing up the z-axis
+    fn sample_bsdf(material: &Material) -> (Bsdf, Vector) {
+        let bsdf = Bsdf::new(
+            material,
+            &Vector::new(0.0, 0.0, 1.0),
+            &Vector::new(1.0, 0.0, 0.0),
+            true,
+        );
+
+        (bsdf, Vector::new(0.3, 0.2, -1.0).normalize())
+    }
+
+    fn lobes() -> Vec<(&'static str, Material)> {
+        let base = Material::principled(Vector::new(0.8, 0.6, 0.4), 0.5);
+
+        vec![
+            ("diffuse", base),
+            ("sheen", Material { sheen: 1.0, ..base }),
+            (
+                "metal",
+                Material {
+                    metallic: 1.0,
+                    anisotropy: 0.8,
+                    ..base
+                },
+            ),
+            (
+                "clearcoat",
+                Material {
+                    metallic: 1.0,
+                    clearcoat: 1.0,
+                    clearcoat_roughness: 0.4,
+                    ..base
+                },
+            ),
+            (
+                "glass",
+                Material {
+                    transmission: 1.0,
+                    ..base
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn sampled_weight_is_value_over_pdf() {
+        for (name, material) in lobes() {
+            let (bsdf, direction) = sample_bsdf(&material);
+            let outgoing = bsdf.local(&(direction * -1.0));
+            let mut random = Random::new(11);
+
+            for _ in 0..1000 {
+                let Some(sample) = bsdf.sample(&direction, &mut random) else {
+                    continue;
+                };
+                let incoming = bsdf.local(&sample.direction);
+                let (value, pdf) = bsdf.evaluate_local(&outgoing, &incoming);
+                let expected = value * (incoming.z.abs() / pdf);
+
+                assert!(
+                    (sample.weight - expected).magnitude() <= 1e-9 * expected.magnitude().max(1.0),
+                    "{name}: {:?} and {expected:?}",
+                    sample.weight
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_matches_the_sampled_directions() {
+        // If the pdf is the density the directions are sampled with, the mean weight is the same as
+        // the value integrated over directions picked evenly on the sphere
+        let count = 500_000;
+        for (name, material) in lobes() {
+            let (bsdf, direction) = sample_bsdf(&material);
+            let outgoing = bsdf.local(&(direction * -1.0));
+            let mut random = Random::new(5);
+
+            let mut sampled = Vector::default();
+            for _ in 0..count {
+                if let Some(sample) = bsdf.sample(&direction, &mut random) {
+                    sampled += sample.weight;
+                }
+            }
+
+            let mut uniform = Vector::default();
+            for _ in 0..count {
+                let z = 1.0 - 2.0 * random.random_value();
+                let phi = 2.0 * PI * random.random_value();
+                let r = (1.0 - z * z).max(0.0).sqrt();
+                let incoming = Vector::new(r * phi.cos(), r * phi.sin(), z);
+                let (value, _) = bsdf.evaluate_local(&outgoing, &incoming);
+                uniform += value * (z.abs() * 4.0 * PI);
+            }
+
+            let (sampled, uniform) = (sampled / count as f64, uniform / count as f64);
+            assert!(
+                (sampled - uniform).magnitude() < 0.03 * uniform.magnitude(),
+                "{name}: {sampled:?} and {uniform:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{init_panic_hook, ray::Ray, vector::Vector};
+
+// Rust Camera struct
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Camera {
+    pub position: Vector,
+    pub direction: Vector, // Direction the camera is looking in
+    pub up: Vector,        // Up direction of the camera, the y-axis points down in the scene
+    pub fov: f64,          // Horizontal field of view in degrees
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera {
+            position: Vector::default(),
+            direction: Vector::new(0.0, 0.0, -1.0),
+            up: Vector::new(0.0, -1.0, 0.0),
+            fov: 90.0,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Camera {
+    #[wasm_bindgen(constructor)]
+    pub fn new(position: Vector, direction: Vector, up: Vector, fov: f64) -> Camera {
+        init_panic_hook();
+
+        Camera {
+            position,
+            direction,
+            up,
+            fov,
+        }
+    }
+
+    // Method to get the ray through a point on the canvas
+    pub fn get_ray(&self, x: f64, y: f64, width: f64, height: f64) -> Ray {
+        let aspect_ratio = width / height;
+        let half_width = (self.fov.to_radians() / 2.0).tan();
+
+        // Calculate the basis of the camera, rows of the canvas go down
+        let forward = self.direction.normalize();
+        let right = self.up.cross(&forward).normalize();
+        let down = right.cross(&forward);
+
+        let u = ((x / width) * 2.0 - 1.0) * half_width;
+        let v = ((y / height) * 2.0 - 1.0) * half_width / aspect_ratio;
+
+        Ray::new(self.position, forward + right * u + down * v)
+    }
+}
+
+impl Camera {
+    // Angle between the rays through neighbouring pixels
+    pub fn pixel_spread(&self, width: f64) -> f64 {
+        2.0 * (self.fov.to_radians() / 2.0).tan() / width
+    }
+}
@@ -0,0 +1,159 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    csg::{convex_crossings, Solid},
+    init_panic_hook,
+    intersection::{angle_tangent, project_on_surface, Intersectable},
+    material::Material,
+    ray::Ray,
+    solver::{solve_quadratic, Roots},
+    vector::Vector,
+};
+
+// Rust Capsule struct, all points within the radius of the segment between start and end
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Capsule {
+    pub start: Vector,
+    pub end: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Capsule {
+    // Method to get the closest point on the segment
+    fn closest_point(&self, point: &Vector) -> Vector {
+        let segment = self.end - self.start;
+        let length_sqrd = segment.dot(&segment);
+        if length_sqrd == 0.0 {
+            return self.start;
+        }
+
+        let h = ((*point - self.start).dot(&segment) / length_sqrd).clamp(0.0, 1.0);
+        self.start + segment * h
+    }
+
+    // Method to get every t along the line of the ray where it crosses the cylinder or the end spheres
+    fn surface_crossings(&self, ray: &Ray) -> [Option<f64>; 6] {
+        let segment = self.end - self.start;
+        let length_sqrd = segment.dot(&segment);
+
+        let oc = ray.origin - self.start;
+        let d_axis = ray.direction.dot(&segment);
+        let o_axis = oc.dot(&segment);
+
+        // Intersect the cylinder between the two end points
+        let side = if length_sqrd > 0.0 {
+            solve_quadratic(
+                length_sqrd * ray.direction.dot(&ray.direction) - d_axis * d_axis,
+                2.0 * (length_sqrd * oc.dot(&ray.direction) - o_axis * d_axis),
+                length_sqrd * (oc.dot(&oc) - self.radius * self.radius) - o_axis * o_axis,
+            )
+        } else {
+            None
+        };
+        let [side_1, side_2] = side.map_or([None; 2], |(t_1, t_2)| {
+            [t_1, t_2].map(|t| {
+                (0.0..=length_sqrd)
+                    .contains(&(o_axis + t * d_axis))
+                    .then_some(t)
+            })
+        });
+
+        // Intersect the spheres at both ends, parts inside the cylinder are never the closest
+        let [start, end] = [self.start, self.end].map(|center| {
+            let oc = ray.origin - center;
+            solve_quadratic(
+                ray.direction.dot(&ray.direction),
+                2.0 * oc.dot(&ray.direction),
+                oc.dot(&oc) - self.radius * self.radius,
+            )
+            .map_or([None; 2], |(t_1, t_2)| [Some(t_1), Some(t_2)])
+        });
+
+        [side_1, side_2, start[0], start[1], end[0], end[1]]
+    }
+}
+
+impl Intersectable for Capsule {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        // Crossings of the end spheres can be inside the capsule, so only use where it enters and leaves
+        self.crossings(ray)
+            .iter()
+            .copied()
+            .find(|t| ray.contains(*t))
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        (*point - self.closest_point(point)).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // u goes around the segment and v from the start to the end, including the rounded ends
+        let segment = self.end - self.start;
+        let length = segment.magnitude();
+        let axis = if length > 0.0 {
+            segment / length
+        } else {
+            Vector::new(0.0, 1.0, 0.0)
+        };
+        let (tangent, bitangent) = axis.orthonormal_basis();
+        let offset = *point - self.start;
+
+        let u = 0.5 + offset.dot(&bitangent).atan2(offset.dot(&tangent)) / (2.0 * PI);
+        let v = (offset.dot(&axis) + self.radius) / (length + 2.0 * self.radius);
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let segment = self.end - self.start;
+        let length = segment.magnitude();
+        let axis = if length > 0.0 {
+            segment / length
+        } else {
+            Vector::new(0.0, 1.0, 0.0)
+        };
+        let offset = *point - self.start;
+
+        // v goes along the segment, which only follows the surface on the cylinder part
+        let normal = self.calculate_normal(point);
+        let dpdv = project_on_surface(&(axis * (length + 2.0 * self.radius)), &normal);
+
+        (angle_tangent(&axis, &offset), dpdv)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let half_size = Vector::new(self.radius, self.radius, self.radius);
+
+        Aabb::from_center(self.start, half_size).union(&Aabb::from_center(self.end, half_size))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+impl Solid for Capsule {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        convex_crossings(self.surface_crossings(ray).into_iter().flatten())
+    }
+}
+
+#[wasm_bindgen]
+impl Capsule {
+    #[wasm_bindgen(constructor)]
+    pub fn new(start: Vector, end: Vector, radius: f64, material: Material) -> Capsule {
+        init_panic_hook();
+
+        Capsule {
+            start,
+            end,
+            radius,
+            material,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::vector::Vector;
+
+// Measured indices of refraction of metals at red, green and blue, as eta and k
+const METALS: [(&str, [f64; 3], [f64; 3]); 8] = [
+    ("aluminum", [1.657, 0.880, 0.521], [9.224, 6.270, 4.837]),
+    ("chromium", [4.368, 2.910, 1.654], [5.203, 4.231, 3.755]),
+    ("copper", [0.200, 0.924, 1.102], [3.912, 2.452, 2.142]),
+    ("gold", [0.143, 0.374, 1.442], [3.983, 2.385, 1.603]),
+    ("iron", [2.912, 2.950, 2.585], [3.089, 2.932, 2.767]),
+    ("platinum", [2.375, 2.085, 1.845], [4.265, 3.715, 3.137]),
+    ("silver", [0.155, 0.117, 0.138], [4.828, 3.122, 2.147]),
+    ("titanium", [2.745, 2.541, 2.267], [3.814, 3.435, 3.039]),
+];
+
+// Rust Conductor struct, the complex index of refraction of a metal per color channel
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Conductor {
+    pub eta: Vector, // Real part
+    pub k: Vector,   // Imaginary part, how quickly light is absorbed
+}
+
+impl Conductor {
+    // Method to look up a metal from the table by its name
+    pub fn named(name: &str) -> Option<Conductor> {
+        let name = name.trim().to_lowercase();
+        let name = if name == "aluminium" {
+            "aluminum"
+        } else {
+            &name
+        };
+
+        METALS
+            .iter()
+            .find(|(metal, _, _)| *metal == name)
+            .map(|(_, eta, k)| Conductor {
+                eta: Vector::new(eta[0], eta[1], eta[2]),
+                k: Vector::new(k[0], k[1], k[2]),
+            })
+    }
+
+    pub fn names() -> Vec<&'static str> {
+        METALS.iter().map(|(name, _, _)| *name).collect()
+    }
+
+    // Method to get the reflectance of every channel for light hitting the surface at the angle
+    pub fn fresnel(&self, cos_theta: f64) -> Vector {
+        Vector::new(
+            fresnel_conductor(cos_theta, self.eta.x, self.k.x),
+            fresnel_conductor(cos_theta, self.eta.y, self.k.y),
+            fresnel_conductor(cos_theta, self.eta.z, self.k.z),
+        )
+    }
+}
+
+// Exact Fresnel reflectance of unpolarized light on a conductor, written without complex numbers
+fn fresnel_conductor(cos_theta: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_theta.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+
+    let t0 = eta * eta - k * k - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let a = ((a2_plus_b2 + t0) / 2.0).max(0.0).sqrt();
+    let t1 = 2.0 * a * cos2.sqrt();
+
+    // Perpendicular and parallel polarizations
+    let rs = (a2_plus_b2 + cos2 - t1) / (a2_plus_b2 + cos2 + t1);
+    let t2 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t3 = t1 * sin2;
+    let rp = rs * (t2 - t3) / (t2 + t3);
+
+    (rs + rp) / 2.0
+}
@@ -0,0 +1,162 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::{disk_extent, Aabb},
+    csg::{convex_crossings, Solid},
+    init_panic_hook,
+    intersection::{angle_tangent, project_on_surface, Intersectable},
+    material::Material,
+    ray::Ray,
+    solver::{solve_quadratic, Roots},
+    vector::Vector,
+};
+
+// Rust Cone struct, a finite cone from the center of its base along the axis to its tip
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Cone {
+    pub base: Vector,
+    pub axis: Vector,
+    pub radius: f64, // Radius of the base
+    pub height: f64,
+    pub capped: bool, // Whether the base is closed by a disk
+    pub material: Material,
+}
+
+impl Cone {
+    // Method to get every t along the line of the ray where it crosses the side or the base
+    fn surface_crossings(&self, ray: &Ray) -> [Option<f64>; 3] {
+        let axis = self.axis.normalize();
+        let slope = self.radius / self.height;
+        let slope_sqrd = slope * slope;
+
+        let oc = ray.origin - self.base;
+        let d_axis = ray.direction.dot(&axis);
+        let o_axis = oc.dot(&axis);
+        let to_tip = self.height - o_axis;
+
+        // The side satisfies |q|^2 - y^2 = slope^2 * (height - y)^2 for the offset q from the base
+        let [side_1, side_2] = solve_quadratic(
+            ray.direction.dot(&ray.direction) - d_axis * d_axis * (1.0 + slope_sqrd),
+            2.0 * (oc.dot(&ray.direction) - o_axis * d_axis + slope_sqrd * to_tip * d_axis),
+            oc.dot(&oc) - o_axis * o_axis - slope_sqrd * to_tip * to_tip,
+        )
+        .map_or([None; 2], |(t_1, t_2)| {
+            [t_1, t_2].map(|t| {
+                (0.0..=self.height)
+                    .contains(&(o_axis + t * d_axis))
+                    .then_some(t)
+            })
+        });
+
+        // Intersect the base
+        let t = -o_axis / d_axis;
+        let radial = oc + ray.direction * t;
+        let inside = radial.dot(&radial) <= self.radius * self.radius;
+        let base = (self.capped && d_axis != 0.0 && inside).then_some(t);
+
+        [side_1, side_2, base]
+    }
+}
+
+impl Intersectable for Cone {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let t = self
+            .surface_crossings(ray)
+            .into_iter()
+            .flatten()
+            .filter(|t| ray.contains(*t))
+            .fold(f64::INFINITY, f64::min);
+
+        if t.is_finite() {
+            return Some(t);
+        }
+
+        // Ray does not intersect the cone
+        None
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        let axis = self.axis.normalize();
+        let offset = *point - self.base;
+        let height = offset.dot(&axis);
+        let radial = offset - axis * height;
+
+        if self.capped && height.abs() < 1e-9 * self.height.max(1.0) {
+            return axis * -1.0;
+        }
+
+        // Gradient of the distance to the side, tilted towards the tip
+        (radial.normalize() + axis * (self.radius / self.height)).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // u goes around the axis and v from the base to the tip
+        let axis = self.axis.normalize();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+        let offset = *point - self.base;
+
+        let u = 0.5 + offset.dot(&bitangent).atan2(offset.dot(&tangent)) / (2.0 * PI);
+        let v = offset.dot(&axis) / self.height;
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let axis = self.axis.normalize();
+        let offset = *point - self.base;
+        let radial = project_on_surface(&offset, &axis).normalize();
+
+        // Up the side towards the tip, or away from the axis on the cap
+        let dpdv = if self.calculate_normal(point).dot(&axis) < -0.99 {
+            radial * self.radius
+        } else {
+            axis * self.height - radial * self.radius
+        };
+
+        (angle_tangent(&axis, &offset), dpdv)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let axis = self.axis.normalize();
+        let tip = self.base + axis * self.height;
+
+        Aabb::from_center(self.base, disk_extent(&axis, self.radius)).include_point(&tip)
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+impl Solid for Cone {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        convex_crossings(self.surface_crossings(ray).into_iter().flatten())
+    }
+}
+
+#[wasm_bindgen]
+impl Cone {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        base: Vector,
+        axis: Vector,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: Material,
+    ) -> Cone {
+        init_panic_hook();
+
+        Cone {
+            base,
+            axis,
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::{ops::Deref, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    capsule::Capsule,
+    cone::Cone,
+    cube::Cube,
+    cylinder::Cylinder,
+    init_panic_hook,
+    instance::Instance,
+    intersection::{Intersectable, Intersection},
+    material::Material,
+    object::Shape,
+    ray::Ray,
+    solver::Roots,
+    sphere::Sphere,
+    torus::Torus,
+    vector::Vector,
+};
+
+// Closed objects that can be combined, they know every point where a line crosses their surface
+pub(crate) trait Solid {
+    // Values of t along the whole line, not only in front of the origin, in ascending order
+    fn crossings(&self, ray: &Ray) -> Roots;
+}
+
+// Rust CsgOperation enum
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    fn contains(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+// Most spans of a ray inside one solid that are kept apart, the ones furthest along the ray are merged into the last
+const MAX_SPANS: usize = 16;
+
+// Rust Boundary struct, where a ray enters or leaves a solid
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Boundary {
+    pub t: f64,
+    pub left: bool,    // Whether the surface belongs to the left child of a CsgNode
+    pub flipped: bool, // Whether the normal of that surface points into the solid
+}
+
+// Rust Span struct, the part of a ray that is inside a solid
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Span {
+    pub enter: Boundary,
+    pub exit: Boundary,
+}
+
+// Rust Spans struct, the spans of a ray in order along it, kept in place so that nothing is allocated
+#[derive(Debug, Copy, Clone, Default)]
+pub(crate) struct Spans {
+    spans: [Span; MAX_SPANS],
+    len: usize,
+}
+
+impl Spans {
+    fn push(&mut self, span: Span) {
+        if self.len < MAX_SPANS {
+            self.spans[self.len] = span;
+            self.len += 1;
+        } else {
+            // Fill the gaps instead of losing the solid, the surfaces nearest along the ray stay exact
+            self.spans[MAX_SPANS - 1].exit = span.exit;
+        }
+    }
+
+    // Method to get the boundaries of all spans in order along the ray
+    fn boundaries(&self) -> impl Iterator<Item = Boundary> + '_ {
+        self.iter().flat_map(|span| [span.enter, span.exit])
+    }
+
+    // Method to get each t where the ray enters or leaves with whether it enters
+    fn events(&self) -> impl Iterator<Item = (f64, bool)> + '_ {
+        self.iter()
+            .flat_map(|span| [(span.enter.t, true), (span.exit.t, false)])
+    }
+}
+
+impl Deref for Spans {
+    type Target = [Span];
+
+    fn deref(&self) -> &[Span] {
+        &self.spans[..self.len]
+    }
+}
+
+// Method to keep only where the line enters and leaves a convex solid
+pub(crate) fn convex_crossings(crossings: impl IntoIterator<Item = f64>) -> Roots {
+    let (count, t_min, t_max) = crossings.into_iter().fold(
+        (0, f64::INFINITY, f64::NEG_INFINITY),
+        |(count, t_min, t_max), t| (count + 1, t_min.min(t), t_max.max(t)),
+    );
+
+    Roots::from((count >= 2).then_some((t_min, t_max)))
+}
+
+// Method to turn the crossings of a single surface into spans
+pub(crate) fn spans_from_crossings(crossings: &[f64]) -> Spans {
+    let boundary = |t: f64| Boundary {
+        t,
+        left: false,
+        flipped: false,
+    };
+
+    let mut spans = Spans::default();
+    for pair in crossings.chunks_exact(2) {
+        spans.push(Span {
+            enter: boundary(pair[0]),
+            exit: boundary(pair[1]),
+        });
+    }
+
+    spans
+}
+
+// Rust CsgNode struct, two solids combined by a boolean operation
+#[derive(Debug, Clone)]
+pub(crate) struct CsgNode {
+    pub operation: CsgOperation,
+    pub left: Rc<Shape>,
+    pub right: Rc<Shape>,
+}
+
+impl CsgNode {
+    pub fn spans(&self, ray: &Ray) -> Spans {
+        let left = self.left.spans(ray).unwrap_or_default();
+        let right = self.right.spans(ray).unwrap_or_default();
+
+        // Where each child enters and leaves, both are already in order along the ray
+        let mut left_events = left.events().peekable();
+        let mut right_events = right.events().peekable();
+
+        // Walk along the ray and keep the boundaries where the result changes from outside to inside
+        let mut spans = Spans::default();
+        let mut enter: Option<Boundary> = None;
+        let (mut inside_left, mut inside_right) = (false, false);
+
+        loop {
+            // The left child goes first when both have a boundary at the same t
+            let is_left = match (left_events.peek(), right_events.peek()) {
+                (Some((t_left, _)), Some((t_right, _))) => t_left <= t_right,
+                (next_left, _) => next_left.is_some(),
+            };
+            let next = if is_left {
+                left_events.next()
+            } else {
+                right_events.next()
+            };
+            let Some((t, entering)) = next else {
+                break;
+            };
+
+            let was_inside = self.operation.contains(inside_left, inside_right);
+            if is_left {
+                inside_left = entering;
+            } else {
+                inside_right = entering;
+            }
+            let is_inside = self.operation.contains(inside_left, inside_right);
+
+            if was_inside == is_inside {
+                continue;
+            }
+
+            // Entering the result by leaving a child means the surface faces the other way
+            let boundary = Boundary {
+                t,
+                left: is_left,
+                flipped: is_inside != entering,
+            };
+
+            if is_inside {
+                enter = Some(boundary);
+            } else if let Some(enter) = enter.take() {
+                spans.push(Span {
+                    enter,
+                    exit: boundary,
+                });
+            }
+        }
+
+        spans
+    }
+}
+
+impl Intersectable for CsgNode {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        // The first boundary within the ray, which is the exit if the origin is inside
+        self.spans(ray)
+            .boundaries()
+            .map(|boundary| boundary.t)
+            .find(|t| ray.contains(*t))
+    }
+
+    fn intersection(&self, ray: &Ray, t: f64) -> Intersection<'_> {
+        // Find the boundary of the hit again to know whose surface it is
+        let boundary = self
+            .spans(ray)
+            .boundaries()
+            .find(|boundary| boundary.t >= t)
+            .unwrap_or(Boundary {
+                t,
+                left: true,
+                flipped: false,
+            });
+        let child = if boundary.left {
+            &self.left
+        } else {
+            &self.right
+        };
+
+        // A flipped surface faces the other way, the normals already face against the ray
+        let intersection = child.intersection(ray, boundary.t);
+        Intersection {
+            front_face: intersection.front_face != boundary.flipped,
+            ..intersection
+        }
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        // The exact surface is only known per ray and is found by intersection
+        self.left.calculate_normal(point)
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        self.left.calculate_uv(point)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        self.left.calculate_tangents(point)
+    }
+
+    fn bounds(&self) -> Aabb {
+        match self.operation {
+            CsgOperation::Union => self.left.bounds().union(&self.right.bounds()),
+            CsgOperation::Intersection | CsgOperation::Difference => self.left.bounds(),
+        }
+    }
+
+    fn get_material(&self) -> &Material {
+        self.left.get_material()
+    }
+}
+
+// Rust Csg struct, used from JavaScript to build a tree of solids. A ray keeps the first 16 spans it has
+// inside each solid of the tree apart, past that the gaps between them are filled
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Csg {
+    shape: Rc<Shape>,
+}
+
+impl Csg {
+    pub(crate) fn shape(&self) -> &Shape {
+        &self.shape
+    }
+
+    fn from_shape(shape: Shape) -> Csg {
+        init_panic_hook();
+
+        Csg {
+            shape: Rc::new(shape),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Csg {
+    pub fn sphere(sphere: Sphere) -> Csg {
+        Csg::from_shape(Shape::Sphere(sphere))
+    }
+
+    pub fn cube(cube: Cube) -> Csg {
+        Csg::from_shape(Shape::Cube(cube))
+    }
+
+    // The cylinder has to be capped to be a closed solid
+    pub fn cylinder(cylinder: Cylinder) -> Csg {
+        Csg::from_shape(Shape::Cylinder(cylinder))
+    }
+
+    // The cone has to be capped to be a closed solid
+    pub fn cone(cone: Cone) -> Csg {
+        Csg::from_shape(Shape::Cone(cone))
+    }
+
+    pub fn capsule(capsule: Capsule) -> Csg {
+        Csg::from_shape(Shape::Capsule(capsule))
+    }
+
+    pub fn torus(torus: Torus) -> Csg {
+        Csg::from_shape(Shape::Torus(torus))
+    }
+
+    pub fn instance(instance: &Instance) -> Csg {
+        Csg::from_shape(Shape::Instance(instance.clone()))
+    }
+
+    pub fn combine(&self, operation: CsgOperation, other: &Csg) -> Csg {
+        Csg::from_shape(Shape::Csg(CsgNode {
+            operation,
+            left: self.shape.clone(),
+            right: other.shape.clone(),
+        }))
+    }
+
+    pub fn union(&self, other: &Csg) -> Csg {
+        self.combine(CsgOperation::Union, other)
+    }
+
+    pub fn intersection(&self, other: &Csg) -> Csg {
+        self.combine(CsgOperation::Intersection, other)
+    }
+
+    // Carve the other solid out of this one
+    pub fn difference(&self, other: &Csg) -> Csg {
+        self.combine(CsgOperation::Difference, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two unit spheres overlapping between x = -0.5 and x = 0.5, told apart by their color
+    fn spheres() -> (Csg, Csg) {
+        let sphere = |x: f64| {
+            let material = Material::new(Vector::new(x, 0.0, 0.0), 1.0, Vector::default(), 0.0);
+            Csg::sphere(Sphere::new(Vector::new(x, 0.0, 0.0), 1.0, material))
+        };
+
+        (sphere(-0.5), sphere(0.5))
+    }
+
+    // Ray along the x-axis from x = -5, t is the distance travelled
+    fn ray_from_the_left() -> Ray {
+        Ray::new(Vector::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0))
+    }
+
+    fn assert_spans(csg: &Csg, ray: &Ray, expected: &[(f64, f64)]) {
+        let spans = csg.shape().spans(ray).unwrap();
+        let spans: Vec<_> = spans
+            .iter()
+            .map(|span| (span.enter.t, span.exit.t))
+            .collect();
+
+        assert_eq!(spans.len(), expected.len(), "{spans:?}");
+        for ((enter, exit), (expected_enter, expected_exit)) in spans.iter().zip(expected) {
+            assert!((enter - expected_enter).abs() < 1e-9, "{spans:?}");
+            assert!((exit - expected_exit).abs() < 1e-9, "{spans:?}");
+        }
+    }
+
+    fn hit<'a>(csg: &'a Csg, ray: &Ray) -> Intersection<'a> {
+        let t = csg.shape().intersect(ray).unwrap();
+
+        csg.shape().intersection(ray, t)
+    }
+
+    #[test]
+    fn union_spans_both_spheres() {
+        let (left, right) = spheres();
+        let union = left.union(&right);
+        let ray = ray_from_the_left();
+
+        assert_spans(&union, &ray, &[(3.5, 6.5)]);
+
+        let intersection = hit(&union, &ray);
+        assert!((intersection.t - 3.5).abs() < 1e-9);
+        assert!((intersection.geometric_normal - Vector::new(-1.0, 0.0, 0.0)).magnitude() < 1e-9);
+        assert!(intersection.front_face);
+        assert_eq!(intersection.material.color.x, -0.5);
+    }
+
+    #[test]
+    fn intersection_spans_the_overlap() {
+        let (left, right) = spheres();
+        let overlap = left.intersection(&right);
+        let ray = ray_from_the_left();
+
+        assert_spans(&overlap, &ray, &[(4.5, 5.5)]);
+
+        // The overlap is entered through the surface of the right sphere
+        let intersection = hit(&overlap, &ray);
+        assert!((intersection.t - 4.5).abs() < 1e-9);
+        assert!(intersection.front_face);
+        assert_eq!(intersection.material.color.x, 0.5);
+    }
+
+    #[test]
+    fn spans_past_the_limit_are_merged() {
+        // A row of small spheres along the x-axis gives the ray more spans than are kept apart
+        let material = Material::new(Vector::new(1.0, 1.0, 1.0), 1.0, Vector::default(), 0.0);
+        let count = MAX_SPANS + 4;
+        let row = (1..count).fold(
+            Csg::sphere(Sphere::new(Vector::default(), 0.25, material)),
+            |row, i| {
+                let center = Vector::new(i as f64, 0.0, 0.0);
+                row.union(&Csg::sphere(Sphere::new(center, 0.25, material)))
+            },
+        );
+        let ray = ray_from_the_left();
+
+        // The last span kept reaches to the far side of the last sphere
+        let mut expected: Vec<_> = (0..MAX_SPANS)
+            .map(|i| (i as f64 + 4.75, i as f64 + 5.25))
+            .collect();
+        expected[MAX_SPANS - 1].1 = count as f64 + 4.25;
+        assert_spans(&row, &ray, &expected);
+
+        let intersection = hit(&row, &ray);
+        assert!((intersection.t - 4.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn difference_flips_the_surface_of_the_carved_sphere() {
+        let (left, right) = spheres();
+        let difference = left.difference(&right);
+
+        assert_spans(&difference, &ray_from_the_left(), &[(3.5, 4.5)]);
+
+        // From the right the ray enters the result where it leaves the right sphere
+        let ray = Ray::new(Vector::new(5.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0));
+        assert_spans(&difference, &ray, &[(5.5, 6.5)]);
+
+        let intersection = hit(&difference, &ray);
+        assert!((intersection.t - 5.5).abs() < 1e-9);
+        assert!((intersection.geometric_normal - Vector::new(1.0, 0.0, 0.0)).magnitude() < 1e-9);
+        assert!(intersection.front_face);
+        assert_eq!(intersection.material.color.x, 0.5);
+
+        // From inside the result the ray leaves through the same flipped surface
+        let ray = Ray::new(Vector::new(-1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        let intersection = hit(&difference, &ray);
+        assert!((intersection.t - 0.5).abs() < 1e-9);
+        assert!((intersection.geometric_normal - Vector::new(-1.0, 0.0, 0.0)).magnitude() < 1e-9);
+        assert!(!intersection.front_face);
+    }
+}
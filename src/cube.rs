@@ -1,11 +1,8 @@
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    init_panic_hook,
-    intersection::{Intersectable, Intersection},
-    material::Material,
-    ray::Ray,
-    vector::Vector,
+    aabb::Aabb, csg::Solid, init_panic_hook, intersection::Intersectable, material::Material,
+    ray::Ray, solver::Roots, vector::Vector,
 };
 
 // Rust Cube struct
@@ -17,69 +14,72 @@ pub struct Cube {
     pub material: Material,
 }
 
-impl Intersectable for Cube {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
+impl Cube {
+    // Method to get where the line of the ray enters and leaves the slabs of the box, t_min > t_max if it misses
+    fn slabs(&self, ray: &Ray) -> (f64, f64) {
         let half_size = self.size / 2.0;
 
         // Calculate the minimum and maximum extents along each axis
         let min_bound = self.center - half_size;
         let max_bound = self.center + half_size;
 
-        let inv_direction = Vector {
-            x: 1.0 / ray.direction.x,
-            y: 1.0 / ray.direction.y,
-            z: 1.0 / ray.direction.z,
-        };
-
-        // Calculate the intersection distances along each axis
-        let t1 = (min_bound.x - ray.origin.x) * inv_direction.x;
-        let t2 = (max_bound.x - ray.origin.x) * inv_direction.x;
-
-        let t_min = t1.min(t2);
-        let t_max = t1.max(t2);
+        let mut t_min = f64::NEG_INFINITY;
+        let mut t_max = f64::INFINITY;
+
+        for (origin, direction, min, max) in [
+            (ray.origin.x, ray.direction.x, min_bound.x, max_bound.x),
+            (ray.origin.y, ray.direction.y, min_bound.y, max_bound.y),
+            (ray.origin.z, ray.direction.z, min_bound.z, max_bound.z),
+        ] {
+            // A ray parallel to the slab is either always or never between its planes
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return (f64::INFINITY, f64::NEG_INFINITY);
+                }
+                continue;
+            }
 
-        let t3 = (min_bound.y - ray.origin.y) * inv_direction.y;
-        let t4 = (max_bound.y - ray.origin.y) * inv_direction.y;
+            // Calculate the intersection distances along the axis
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
 
-        let t_min = t3.min(t4).max(t_min);
-        let t_max = t3.max(t4).min(t_max);
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
 
-        let t5 = (min_bound.z - ray.origin.z) * inv_direction.z;
-        let t6 = (max_bound.z - ray.origin.z) * inv_direction.z;
+        (t_min, t_max)
+    }
+}
 
-        let t_min = t5.min(t6).max(t_min);
-        let t_max = t5.max(t6).min(t_max);
+impl Intersectable for Cube {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let (t_min, t_max) = self.slabs(ray);
 
-        // Check if there is a valid intersection
-        if t_min <= t_max && t_min >= 0.0 {
-            // Return the intersection point at the minmum distance
-            let intersection_point = ray.point_at_parameter(t_min);
-            return Some(Intersection {
-                t: t_min,
-                intersection_point,
-                intersection_object: Box::new(*self),
-            });
+        // Ray does not intersect with the cube
+        if t_min > t_max {
+            return None;
         }
 
-        // Ray does not intersect with the cube
-        None
+        // The entry point, or the exit point if the ray starts inside the cube
+        [t_min, t_max].into_iter().find(|t| ray.contains(*t))
     }
 
     fn calculate_normal(&self, point: &Vector) -> Vector {
-        // Calculate the differences between the point's coordinates and the cube's center
-        let dx = point.x - self.center.x;
-        let dy = point.y - self.center.y;
-        let dz = point.z - self.center.z;
+        // Calculate the differences between the point's coordinates and the cube's center,
+        // relative to the half size so every face is at 1 whatever the extents of the cube
+        let dx = (point.x - self.center.x) / (self.size.x / 2.0);
+        let dy = (point.y - self.center.y) / (self.size.y / 2.0);
+        let dz = (point.z - self.center.z) / (self.size.z / 2.0);
 
         // Identify the face closest to the point and assign the normal accordingly
-        if dx.abs() > dy.abs() && dx.abs() > dz.abs() {
+        if dx.abs() >= dy.abs() && dx.abs() >= dz.abs() {
             // Point is on the face with the largest x-coordinate differance
             Vector {
                 x: dx.signum(),
                 y: 0.0,
                 z: 0.0,
             }
-        } else if dy.abs() > dz.abs() {
+        } else if dy.abs() >= dz.abs() {
             // Point is on the face with the largest y-coordinate differance
             Vector {
                 x: 0.0,
@@ -96,11 +96,60 @@ impl Intersectable for Cube {
         }
     }
 
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Position on the face the point is on, between 0 and 1 along both edges of the face
+        let normal = self.calculate_normal(point);
+        let local = *point - self.center;
+        let u = |offset: f64, size: f64| offset / size + 0.5;
+
+        if normal.x != 0.0 {
+            (u(local.z, self.size.z), u(local.y, self.size.y))
+        } else if normal.y != 0.0 {
+            (u(local.x, self.size.x), u(local.z, self.size.z))
+        } else {
+            (u(local.x, self.size.x), u(local.y, self.size.y))
+        }
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        // Along the edges of the face the point is on, in the same order as the texture coordinates
+        let normal = self.calculate_normal(point);
+
+        if normal.x != 0.0 {
+            (
+                Vector::new(0.0, 0.0, self.size.z),
+                Vector::new(0.0, self.size.y, 0.0),
+            )
+        } else if normal.y != 0.0 {
+            (
+                Vector::new(self.size.x, 0.0, 0.0),
+                Vector::new(0.0, 0.0, self.size.z),
+            )
+        } else {
+            (
+                Vector::new(self.size.x, 0.0, 0.0),
+                Vector::new(0.0, self.size.y, 0.0),
+            )
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.center, self.size / 2.0)
+    }
+
     fn get_material(&self) -> &Material {
         &self.material
     }
 }
 
+impl Solid for Cube {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        let (t_min, t_max) = self.slabs(ray);
+
+        Roots::from((t_min <= t_max).then_some((t_min, t_max)))
+    }
+}
+
 #[wasm_bindgen]
 impl Cube {
     #[wasm_bindgen(constructor)]
@@ -0,0 +1,164 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::{disk_extent, Aabb},
+    csg::{convex_crossings, Solid},
+    init_panic_hook,
+    intersection::{angle_tangent, project_on_surface, Intersectable},
+    material::Material,
+    ray::Ray,
+    solver::{solve_quadratic, Roots},
+    vector::Vector,
+};
+
+// Rust Cylinder struct, a finite cylinder around an axis through its center
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Cylinder {
+    pub center: Vector,
+    pub axis: Vector,
+    pub radius: f64,
+    pub height: f64,
+    pub capped: bool, // Whether the ends are closed by disks
+    pub material: Material,
+}
+
+impl Cylinder {
+    // Method to get every t along the line of the ray where it crosses the side or the caps
+    fn surface_crossings(&self, ray: &Ray) -> [Option<f64>; 4] {
+        let axis = self.axis.normalize();
+        let half_height = self.height / 2.0;
+
+        // Work with the parts of the ray perpendicular to the axis
+        let oc = ray.origin - self.center;
+        let d_axis = ray.direction.dot(&axis);
+        let o_axis = oc.dot(&axis);
+        let d_perp = ray.direction - axis * d_axis;
+        let o_perp = oc - axis * o_axis;
+
+        // Intersect the side of the cylinder within the height
+        let [side_1, side_2] = solve_quadratic(
+            d_perp.dot(&d_perp),
+            2.0 * d_perp.dot(&o_perp),
+            o_perp.dot(&o_perp) - self.radius * self.radius,
+        )
+        .map_or([None; 2], |(t_1, t_2)| {
+            [t_1, t_2].map(|t| ((o_axis + t * d_axis).abs() <= half_height).then_some(t))
+        });
+
+        // Intersect the caps at both ends
+        let [cap_1, cap_2] = [-half_height, half_height].map(|end| {
+            let t = (end - o_axis) / d_axis;
+            let radial = o_perp + d_perp * t;
+            let inside = radial.dot(&radial) <= self.radius * self.radius;
+            (self.capped && d_axis != 0.0 && inside).then_some(t)
+        });
+
+        [side_1, side_2, cap_1, cap_2]
+    }
+}
+
+impl Intersectable for Cylinder {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let t = self
+            .surface_crossings(ray)
+            .into_iter()
+            .flatten()
+            .filter(|t| ray.contains(*t))
+            .fold(f64::INFINITY, f64::min);
+
+        if t.is_finite() {
+            return Some(t);
+        }
+
+        // Ray does not intersect the cylinder
+        None
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        let axis = self.axis.normalize();
+        let offset = *point - self.center;
+        let height = offset.dot(&axis);
+        let radial = offset - axis * height;
+
+        // Points on the caps are further along the axis than they are from the side
+        let cap_distance = self.height / 2.0 - height.abs();
+        let side_distance = self.radius - radial.magnitude();
+        if self.capped && cap_distance < side_distance {
+            axis * height.signum()
+        } else {
+            radial.normalize()
+        }
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // u goes around the axis and v along it
+        let axis = self.axis.normalize();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+        let offset = *point - self.center;
+
+        let u = 0.5 + offset.dot(&bitangent).atan2(offset.dot(&tangent)) / (2.0 * PI);
+        let v = offset.dot(&axis) / self.height + 0.5;
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let axis = self.axis.normalize();
+        let offset = *point - self.center;
+        let radial = project_on_surface(&offset, &axis);
+
+        // On the caps v does not change, so use the direction away from the axis instead
+        let dpdv = if self.calculate_normal(point).dot(&axis).abs() > 0.5 {
+            radial.normalize() * self.radius
+        } else {
+            axis * self.height
+        };
+
+        (angle_tangent(&axis, &offset), dpdv)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let axis = self.axis.normalize();
+        let half_axis = axis * (self.height / 2.0);
+        let half_axis = Vector::new(half_axis.x.abs(), half_axis.y.abs(), half_axis.z.abs());
+
+        Aabb::from_center(self.center, half_axis + disk_extent(&axis, self.radius))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+impl Solid for Cylinder {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        convex_crossings(self.surface_crossings(ray).into_iter().flatten())
+    }
+}
+
+#[wasm_bindgen]
+impl Cylinder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        center: Vector,
+        axis: Vector,
+        radius: f64,
+        height: f64,
+        capped: bool,
+        material: Material,
+    ) -> Cylinder {
+        init_panic_hook();
+
+        Cylinder {
+            center,
+            axis,
+            radius,
+            height,
+            capped,
+            material,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::{disk_extent, Aabb},
+    init_panic_hook,
+    intersection::{angle_tangent, Intersectable},
+    material::Material,
+    ray::Ray,
+    vector::Vector,
+};
+
+// Rust Disk struct
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Disk {
+    pub center: Vector,
+    pub normal: Vector,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Intersectable for Disk {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let denominator = self.normal.dot(&ray.direction);
+
+        // Ray is parallel to the disk
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = self.normal.dot(&(self.center - ray.origin)) / denominator;
+
+        if ray.contains(t) {
+            // Check if the point on the plane of the disk is within the radius
+            let intersection_point = ray.point_at_parameter(t);
+            let offset = intersection_point - self.center;
+
+            if offset.dot(&offset) <= self.radius * self.radius {
+                return Some(t);
+            }
+        }
+
+        // Ray does not intersect the disk
+        None
+    }
+
+    fn calculate_normal(&self, _point: &Vector) -> Vector {
+        self.normal.normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Polar coordinates, u is the angle and v the distance from the center
+        let (tangent, bitangent) = self.normal.normalize().orthonormal_basis();
+        let offset = *point - self.center;
+
+        let u = 0.5 + offset.dot(&bitangent).atan2(offset.dot(&tangent)) / (2.0 * PI);
+        let v = offset.magnitude() / self.radius;
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let offset = *point - self.center;
+
+        (
+            angle_tangent(&self.normal.normalize(), &offset),
+            offset.normalize() * self.radius,
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::from_center(self.center, disk_extent(&self.normal, self.radius))
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+#[wasm_bindgen]
+impl Disk {
+    #[wasm_bindgen(constructor)]
+    pub fn new(center: Vector, normal: Vector, radius: f64, material: Material) -> Disk {
+        init_panic_hook();
+
+        Disk {
+            center,
+            normal,
+            radius,
+            material,
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::ImageData;
+
+use crate::{
+    aabb::Aabb, init_panic_hook, intersection::Intersectable, material::Material, ray::Ray,
+    vector::Vector,
+};
+
+// Rust HeightGrid struct, the heights with everything that is precomputed from them
+#[derive(Debug)]
+struct HeightGrid {
+    heights: Vec<f64>,
+    columns: usize,
+    rows: usize,
+    cell_ranges: Vec<(f64, f64)>, // Lowest and highest height of each cell
+    min_height: f64,
+    max_height: f64,
+}
+
+impl HeightGrid {
+    fn new(heights: Vec<f64>, columns: usize, rows: usize) -> HeightGrid {
+        let mut cell_ranges = Vec::with_capacity((columns - 1) * (rows - 1));
+        for row in 0..rows - 1 {
+            for column in 0..columns - 1 {
+                let corners = [
+                    heights[row * columns + column],
+                    heights[row * columns + column + 1],
+                    heights[(row + 1) * columns + column],
+                    heights[(row + 1) * columns + column + 1],
+                ];
+                let low = corners.iter().copied().fold(f64::INFINITY, f64::min);
+                let high = corners.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                cell_ranges.push((low, high));
+            }
+        }
+
+        let min_height = heights.iter().copied().fold(f64::INFINITY, f64::min);
+        let max_height = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        HeightGrid {
+            heights,
+            columns,
+            rows,
+            cell_ranges,
+            min_height,
+            max_height,
+        }
+    }
+
+    fn height(&self, column: usize, row: usize) -> f64 {
+        self.heights[row.min(self.rows - 1) * self.columns + column.min(self.columns - 1)]
+    }
+}
+
+// Rust Heightfield struct, terrain from a grid of heights that go up, which is the negative y direction
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Heightfield {
+    grid: Rc<HeightGrid>,
+    origin: Vector, // Corner with the lowest x and z at height zero
+    size: Vector,   // Extent along x and z, and the scale of the heights
+    material: Material,
+}
+
+impl Heightfield {
+    fn new(
+        heights: Vec<f64>,
+        columns: usize,
+        rows: usize,
+        origin: Vector,
+        size: Vector,
+        material: Material,
+    ) -> Result<Heightfield, JsValue> {
+        // On 32-bit targets like wasm the product of two u32 can overflow usize
+        let count = columns
+            .checked_mul(rows)
+            .ok_or_else(|| JsValue::from_str("A heightfield has too many heights"))?;
+        if columns < 2 || rows < 2 || heights.len() != count {
+            return Err(JsValue::from_str(
+                "A heightfield needs at least 2 by 2 heights and one height for every point of the grid",
+            ));
+        }
+
+        Ok(Heightfield {
+            grid: Rc::new(HeightGrid::new(heights, columns, rows)),
+            origin,
+            size,
+            material,
+        })
+    }
+
+    // Method to bring a vector into the space of the grid, one unit per cell and the height going up
+    fn to_grid_space(&self, vector: &Vector, is_point: bool) -> Vector {
+        let offset = if is_point {
+            *vector - self.origin
+        } else {
+            *vector
+        };
+
+        Vector::new(
+            offset.x / self.size.x * (self.grid.columns - 1) as f64,
+            -offset.y / self.size.y,
+            offset.z / self.size.z * (self.grid.rows - 1) as f64,
+        )
+    }
+
+    // Method to find the cell a point is in and where in the cell it is
+    fn locate(&self, point: &Vector) -> (usize, usize, f64, f64) {
+        let local = self.to_grid_space(point, true);
+        let column = (local.x.floor().max(0.0) as usize).min(self.grid.columns - 2);
+        let row = (local.z.floor().max(0.0) as usize).min(self.grid.rows - 2);
+        let fx = (local.x - column as f64).clamp(0.0, 1.0);
+        let fz = (local.z - row as f64).clamp(0.0, 1.0);
+
+        (column, row, fx, fz)
+    }
+
+    fn grid_point(&self, column: usize, row: usize) -> Vector {
+        Vector::new(column as f64, self.grid.height(column, row), row as f64)
+    }
+
+    // Method to get the normal at a point of the grid in world space from the neighbouring heights
+    fn vertex_normal(&self, column: usize, row: usize) -> Vector {
+        let left = self.grid.height(column.saturating_sub(1), row);
+        let right = self.grid.height(column + 1, row);
+        let back = self.grid.height(column, row.saturating_sub(1));
+        let front = self.grid.height(column, row + 1);
+
+        // Slopes in world units, the world y-axis points down
+        let cell_x = self.size.x / (self.grid.columns - 1) as f64;
+        let cell_z = self.size.z / (self.grid.rows - 1) as f64;
+        let span_x = (column + 1).min(self.grid.columns - 1) - column.saturating_sub(1);
+        let span_z = (row + 1).min(self.grid.rows - 1) - row.saturating_sub(1);
+        let slope_x = (right - left) * self.size.y / (span_x as f64 * cell_x);
+        let slope_z = (front - back) * self.size.y / (span_z as f64 * cell_z);
+
+        Vector::new(-slope_x, -1.0, -slope_z).normalize()
+    }
+
+    // Method to intersect one of the two triangles of a cell in grid space
+    fn intersect_triangle(ray: &Ray, a: &Vector, b: &Vector, c: &Vector) -> Option<f64> {
+        let edge_1 = *b - *a;
+        let edge_2 = *c - *a;
+        let p = ray.direction.cross(&edge_2);
+        let determinant = edge_1.dot(&p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+
+        let inverse = 1.0 / determinant;
+        let s = ray.origin - *a;
+        let u = s.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(&edge_1);
+        let v = ray.direction.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        Some(edge_2.dot(&q) * inverse)
+    }
+
+    fn intersect_cell(&self, ray: &Ray, column: usize, row: usize) -> Option<f64> {
+        let p00 = self.grid_point(column, row);
+        let p10 = self.grid_point(column + 1, row);
+        let p01 = self.grid_point(column, row + 1);
+        let p11 = self.grid_point(column + 1, row + 1);
+
+        [
+            Heightfield::intersect_triangle(ray, &p00, &p10, &p11),
+            Heightfield::intersect_triangle(ray, &p00, &p11, &p01),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|t| ray.contains(*t))
+        .reduce(f64::min)
+    }
+}
+
+impl Intersectable for Heightfield {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let grid = &self.grid;
+        let local_ray = Ray {
+            origin: self.to_grid_space(&ray.origin, true),
+            direction: self.to_grid_space(&ray.direction, false),
+            ..*ray
+        };
+        let o = local_ray.origin;
+        let d = local_ray.direction;
+
+        // Clip the ray to the box around the whole grid
+        let mut t_enter = ray.t_min;
+        let mut t_exit = ray.t_max;
+        for (origin, direction, low, high) in [
+            (o.x, d.x, 0.0, (grid.columns - 1) as f64),
+            (o.y, d.y, grid.min_height, grid.max_height),
+            (o.z, d.z, 0.0, (grid.rows - 1) as f64),
+        ] {
+            if direction == 0.0 {
+                if origin < low || origin > high {
+                    return None;
+                }
+                continue;
+            }
+            let t_1 = (low - origin) / direction;
+            let t_2 = (high - origin) / direction;
+            t_enter = t_enter.max(t_1.min(t_2));
+            t_exit = t_exit.min(t_1.max(t_2));
+        }
+        if t_enter > t_exit {
+            return None;
+        }
+
+        // Walk through the cells along the ray with a grid DDA
+        let start = local_ray.point_at_parameter(t_enter);
+        let last_column = grid.columns as i64 - 2;
+        let last_row = grid.rows as i64 - 2;
+        let mut column = (start.x.floor() as i64).clamp(0, last_column);
+        let mut row = (start.z.floor() as i64).clamp(0, last_row);
+
+        let step_column: i64 = if d.x >= 0.0 { 1 } else { -1 };
+        let step_row: i64 = if d.z >= 0.0 { 1 } else { -1 };
+        let delta_x = (1.0 / d.x).abs();
+        let delta_z = (1.0 / d.z).abs();
+        let next_boundary = |cell: i64, step: i64, origin: f64, direction: f64| {
+            if direction == 0.0 {
+                return f64::INFINITY;
+            }
+            let boundary = if step > 0 { cell + 1 } else { cell } as f64;
+            (boundary - origin) / direction
+        };
+        let mut next_x = next_boundary(column, step_column, o.x, d.x);
+        let mut next_z = next_boundary(row, step_row, o.z, d.z);
+
+        let mut t_cell_start = t_enter;
+        loop {
+            let t_cell_end = next_x.min(next_z).min(t_exit);
+
+            // Skip the cell if the ray stays above or below all of its heights
+            let (low, high) = grid.cell_ranges[row as usize * (grid.columns - 1) + column as usize];
+            let y_start = o.y + d.y * t_cell_start;
+            let y_end = o.y + d.y * t_cell_end;
+            if y_start.min(y_end) <= high && y_start.max(y_end) >= low {
+                if let Some(t) = self.intersect_cell(&local_ray, column as usize, row as usize) {
+                    return Some(t);
+                }
+            }
+
+            if t_cell_end >= t_exit {
+                return None;
+            }
+
+            // Step into the next cell
+            if next_x < next_z {
+                column += step_column;
+                next_x += delta_x;
+            } else {
+                row += step_row;
+                next_z += delta_z;
+            }
+            if column < 0 || column > last_column || row < 0 || row > last_row {
+                return None;
+            }
+            t_cell_start = t_cell_end;
+        }
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        let (column, row, fx, fz) = self.locate(point);
+
+        // Interpolate the normals of the corners of the triangle the point is in
+        let n00 = self.vertex_normal(column, row);
+        let n11 = self.vertex_normal(column + 1, row + 1);
+        let normal = if fx >= fz {
+            n00 * (1.0 - fx) + self.vertex_normal(column + 1, row) * (fx - fz) + n11 * fz
+        } else {
+            n00 * (1.0 - fz) + self.vertex_normal(column, row + 1) * (fz - fx) + n11 * fx
+        };
+
+        normal.normalize()
+    }
+
+    fn calculate_geometric_normal(&self, point: &Vector) -> Vector {
+        let (dpdu, dpdv) = self.calculate_tangents(point);
+
+        dpdu.cross(&dpdv).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        let offset = *point - self.origin;
+
+        (offset.x / self.size.x, offset.z / self.size.z)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let (column, row, fx, fz) = self.locate(point);
+
+        // Slopes of the triangle the point is in, per cell
+        let h00 = self.grid.height(column, row);
+        let h10 = self.grid.height(column + 1, row);
+        let h01 = self.grid.height(column, row + 1);
+        let h11 = self.grid.height(column + 1, row + 1);
+        let (slope_x, slope_z) = if fx >= fz {
+            (h10 - h00, h11 - h10)
+        } else {
+            (h11 - h01, h01 - h00)
+        };
+
+        // Heights go up, which is the negative y direction
+        let cells_x = (self.grid.columns - 1) as f64;
+        let cells_z = (self.grid.rows - 1) as f64;
+
+        (
+            Vector::new(self.size.x, -slope_x * cells_x * self.size.y, 0.0),
+            Vector::new(0.0, -slope_z * cells_z * self.size.y, self.size.z),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::new(
+            Vector::new(
+                self.origin.x,
+                self.origin.y - self.grid.max_height * self.size.y,
+                self.origin.z,
+            ),
+            Vector::new(
+                self.origin.x + self.size.x,
+                self.origin.y - self.grid.min_height * self.size.y,
+                self.origin.z + self.size.z,
+            ),
+        )
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+impl Heightfield {
+    // Point that is moved by set_center, the middle of the grid at height zero
+    pub(crate) fn center(&self) -> Vector {
+        self.origin + Vector::new(self.size.x / 2.0, 0.0, self.size.z / 2.0)
+    }
+
+    pub(crate) fn set_center(&mut self, center: Vector) {
+        self.origin = center - Vector::new(self.size.x / 2.0, 0.0, self.size.z / 2.0);
+    }
+
+    pub(crate) fn set_material(&mut self, material: Material) {
+        self.material = material;
+    }
+}
+
+#[wasm_bindgen]
+impl Heightfield {
+    // The heights are given row by row, rows go along z and columns along x
+    pub fn from_grid(
+        heights: Vec<f64>,
+        columns: u32,
+        rows: u32,
+        origin: Vector,
+        size: Vector,
+        material: Material,
+    ) -> Result<Heightfield, JsValue> {
+        init_panic_hook();
+
+        Heightfield::new(
+            heights,
+            columns as usize,
+            rows as usize,
+            origin,
+            size,
+            material,
+        )
+    }
+
+    // Heights between 0 and 1 from the brightness of an image
+    pub fn from_image(
+        image: &ImageData,
+        origin: Vector,
+        size: Vector,
+        material: Material,
+    ) -> Result<Heightfield, JsValue> {
+        init_panic_hook();
+
+        let heights = image
+            .data()
+            .chunks_exact(4)
+            .map(|pixel| {
+                (0.2126 * pixel[0] as f64 + 0.7152 * pixel[1] as f64 + 0.0722 * pixel[2] as f64)
+                    / 255.0
+            })
+            .collect();
+
+        Heightfield::new(
+            heights,
+            image.width() as usize,
+            image.height() as usize,
+            origin,
+            size,
+            material,
+        )
+    }
+}
@@ -0,0 +1,273 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    capsule::Capsule,
+    cone::Cone,
+    csg::{Csg, Spans},
+    cube::Cube,
+    cylinder::Cylinder,
+    disk::Disk,
+    heightfield::Heightfield,
+    init_panic_hook,
+    intersection::{Intersectable, Intersection},
+    material::Material,
+    object::Shape,
+    plane::Plane,
+    quad::Quad,
+    ray::Ray,
+    sdf::Sdf,
+    sphere::Sphere,
+    torus::Torus,
+    transform::Transform,
+    vector::Vector,
+};
+
+// Rust Instance struct, places shared geometry in the scene with a transform
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Instance {
+    shape: Rc<Shape>,
+    transform: Transform,
+}
+
+impl Instance {
+    pub(crate) fn new(shape: Shape, transform: Transform) -> Instance {
+        Instance {
+            shape: Rc::new(shape),
+            transform,
+        }
+    }
+
+    pub(crate) fn set_material(&mut self, material: Material) {
+        // Copies the geometry if other instances still share it
+        Rc::make_mut(&mut self.shape).set_material(material);
+    }
+
+    // The surfaces of the spans are put under the transform by intersection, t is the same in both spaces
+    pub(crate) fn spans(&self, ray: &Ray) -> Option<Spans> {
+        self.shape.spans(&self.transform.inverse_transform_ray(ray))
+    }
+}
+
+// Method to bring an intersection in the space of the geometry back into the scene
+fn transform_intersection<'a>(
+    local_intersection: Intersection<'a>,
+    ray: &Ray,
+    transform: &Transform,
+) -> Intersection<'a> {
+    Intersection {
+        intersection_point: ray.point_at_parameter(local_intersection.t),
+        geometric_normal: transform
+            .transform_normal(&local_intersection.geometric_normal)
+            .normalize(),
+        shading_normal: transform
+            .transform_normal(&local_intersection.shading_normal)
+            .normalize(),
+        dpdu: transform.transform_vector(&local_intersection.dpdu),
+        dpdv: transform.transform_vector(&local_intersection.dpdv),
+        ..local_intersection
+    }
+}
+
+// Method to get the box around a box after it has been transformed
+fn transform_bounds(local_bounds: &Aabb, transform: &Transform) -> Aabb {
+    if !local_bounds.is_finite() {
+        return Aabb::infinite();
+    }
+
+    // Box around the transformed corners of the box of the geometry
+    let corners = local_bounds.corners();
+    let first = transform.transform_point(&corners[0]);
+
+    corners[1..]
+        .iter()
+        .fold(Aabb::new(first, first), |bounds, corner| {
+            bounds.include_point(&transform.transform_point(corner))
+        })
+}
+
+impl Intersectable for Instance {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        // Intersect in the space of the geometry, t is the same in both spaces
+        self.shape
+            .intersect(&self.transform.inverse_transform_ray(ray))
+    }
+
+    fn intersection(&self, ray: &Ray, t: f64) -> Intersection<'_> {
+        let local_ray = self.transform.inverse_transform_ray(ray);
+        let local_intersection = self.shape.intersection(&local_ray, t);
+
+        transform_intersection(local_intersection, ray, &self.transform)
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        let local_point = self.transform.inverse().transform_point(point);
+        let local_normal = self.shape.calculate_normal(&local_point);
+
+        self.transform.transform_normal(&local_normal).normalize()
+    }
+
+    fn calculate_geometric_normal(&self, point: &Vector) -> Vector {
+        let local_point = self.transform.inverse().transform_point(point);
+        let local_normal = self.shape.calculate_geometric_normal(&local_point);
+
+        self.transform.transform_normal(&local_normal).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        let local_point = self.transform.inverse().transform_point(point);
+
+        self.shape.calculate_uv(&local_point)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let local_point = self.transform.inverse().transform_point(point);
+        let (dpdu, dpdv) = self.shape.calculate_tangents(&local_point);
+
+        (
+            self.transform.transform_vector(&dpdu),
+            self.transform.transform_vector(&dpdv),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        transform_bounds(&self.shape.bounds(), &self.transform)
+    }
+
+    fn get_material(&self) -> &Material {
+        self.shape.get_material()
+    }
+}
+
+#[wasm_bindgen]
+impl Instance {
+    pub fn from_sphere(sphere: Sphere, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Sphere(sphere), transform)
+    }
+
+    pub fn from_cube(cube: Cube, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Cube(cube), transform)
+    }
+
+    pub fn from_plane(plane: Plane, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Plane(plane), transform)
+    }
+
+    pub fn from_disk(disk: Disk, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Disk(disk), transform)
+    }
+
+    pub fn from_quad(quad: Quad, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Quad(quad), transform)
+    }
+
+    pub fn from_cylinder(cylinder: Cylinder, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Cylinder(cylinder), transform)
+    }
+
+    pub fn from_cone(cone: Cone, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Cone(cone), transform)
+    }
+
+    pub fn from_capsule(capsule: Capsule, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Capsule(capsule), transform)
+    }
+
+    pub fn from_torus(torus: Torus, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Torus(torus), transform)
+    }
+
+    pub fn from_csg(csg: &Csg, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(csg.shape().clone(), transform)
+    }
+
+    pub fn from_sdf(sdf: &Sdf, material: Material, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Sdf(sdf.object(material)), transform)
+    }
+
+    pub fn from_heightfield(heightfield: &Heightfield, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Heightfield(heightfield.clone()), transform)
+    }
+
+    // Instance of another instance, the transform is applied after the one of the other instance
+    pub fn from_instance(instance: &Instance, transform: Transform) -> Instance {
+        init_panic_hook();
+
+        Instance::new(Shape::Instance(instance.clone()), transform)
+    }
+
+    // Another instance with a new transform, the geometry is shared and not copied
+    pub fn with_transform(&self, transform: Transform) -> Instance {
+        Instance {
+            shape: self.shape.clone(),
+            transform,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    #[wasm_bindgen(setter)]
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_of_a_torus_is_moved_by_the_transform() {
+        let material = Material::new(Vector::new(1.0, 1.0, 1.0), 1.0, Vector::default(), 0.0);
+        let torus = Torus::new(
+            Vector::default(),
+            Vector::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            material,
+        );
+        let instance =
+            Instance::from_torus(torus, Transform::translate(Vector::new(0.0, 0.0, -5.0)));
+        let ray = Ray::new(Vector::new(-5.0, 0.0, -5.0), Vector::new(1.0, 0.0, 0.0));
+
+        // The ray goes through the middle of the moved ring and hits the outside of the tube first
+        let t = instance.intersect(&ray).unwrap();
+        assert!((t - 2.5).abs() < 1e-9);
+
+        let intersection = instance.intersection(&ray, t);
+        assert!(
+            (intersection.intersection_point - Vector::new(-2.5, 0.0, -5.0)).magnitude() < 1e-9
+        );
+        assert!((intersection.geometric_normal - Vector::new(-1.0, 0.0, 0.0)).magnitude() < 1e-9);
+    }
+}
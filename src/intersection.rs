@@ -1,39 +1,87 @@
-use crate::{material::Material, ray::Ray, vector::Vector};
+use std::f64::consts::PI;
+
+use crate::{aabb::Aabb, material::Material, object::ObjectId, ray::Ray, vector::Vector};
 
 pub(crate) trait Intersectable {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection>;
+    // Nearest t within the ray where it hits the surface
+    fn intersect(&self, ray: &Ray) -> Option<f64>;
     fn calculate_normal(&self, point: &Vector) -> Vector;
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64);
+    // How the point moves when u and v change, dp/du and dp/dv
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector);
+    fn bounds(&self) -> Aabb;
     fn get_material(&self) -> &Material;
+
+    // Everything about the hit at t, only worked out for the closest hit along the ray
+    fn intersection(&self, ray: &Ray, t: f64) -> Intersection<'_> {
+        Intersection::new(self, ray, t)
+    }
+
+    // Normal of the actual surface, only differs from the normal used for shading when that is interpolated
+    fn calculate_geometric_normal(&self, point: &Vector) -> Vector {
+        self.calculate_normal(point)
+    }
+}
+
+// Method to get how a point moves when u goes once around the axis
+pub(crate) fn angle_tangent(axis: &Vector, offset: &Vector) -> Vector {
+    axis.cross(offset) * (2.0 * PI)
+}
+
+// Method to keep only the part of a vector that lies in the surface with the normal
+pub(crate) fn project_on_surface(vector: &Vector, normal: &Vector) -> Vector {
+    *vector - *normal * vector.dot(normal)
 }
 
-// pub(crate) trait IntersectableClone {
-//     fn clone_box(&self) -> Box<dyn Intersectable>;
-// }
-
-// impl<T> IntersectableClone for T
-// where
-//     T: 'static + Intersectable + Clone
-// {
-//     fn clone_box(&self) -> Box<dyn Intersectable> {
-//         Box::new(self.clone())
-//     }
-// }
-
-// impl IntersectableClone for Box<dyn Intersectable> {
-//     fn clone_box(&self) -> Box<dyn Intersectable> {
-//         self.
-//     }
-// }
-
-// impl Clone for Box<dyn Intersectable> {
-//     fn clone(&self) -> Box<dyn Intersectable> {
-//         self.clone()
-//     }
-// }
-
-// Rust Intersection struct
-pub struct Intersection {
+// Rust Intersection struct, everything about a hit with the material borrowed from the object
+#[derive(Debug, Copy, Clone)]
+pub struct Intersection<'a> {
     pub t: f64,
     pub intersection_point: Vector,
-    pub intersection_object: Box<dyn Intersectable>,
+    pub geometric_normal: Vector, // Both normals face against the ray
+    pub shading_normal: Vector,
+    pub dpdu: Vector,
+    pub dpdv: Vector,
+    pub uv: (f64, f64),
+    pub front_face: bool,    // Whether the ray hit the outside of the surface
+    pub object_id: ObjectId, // Filled in by the scene
+    pub material: &'a Material,
+}
+
+impl<'a> Intersection<'a> {
+    // Method to describe the hit at t along the ray on the surface of an object
+    pub(crate) fn new<T: Intersectable + ?Sized>(
+        object: &'a T,
+        ray: &Ray,
+        t: f64,
+    ) -> Intersection<'a> {
+        let intersection_point = ray.point_at_parameter(t);
+        let geometric_normal = object.calculate_geometric_normal(&intersection_point);
+        let shading_normal = object.calculate_normal(&intersection_point);
+
+        // Turn the normals towards where the ray came from
+        let front_face = geometric_normal.dot(&ray.direction) < 0.0;
+        let side = if front_face { 1.0 } else { -1.0 };
+
+        // Any tangents will do where the parameterization of the surface breaks down, like at the poles of a sphere
+        let (dpdu, dpdv) = object.calculate_tangents(&intersection_point);
+        let (dpdu, dpdv) = if dpdu.cross(&dpdv).magnitude() > 1e-12 {
+            (dpdu, dpdv)
+        } else {
+            shading_normal.orthonormal_basis()
+        };
+
+        Intersection {
+            t,
+            intersection_point,
+            geometric_normal: geometric_normal * side,
+            shading_normal: shading_normal * side,
+            dpdu,
+            dpdv,
+            uv: object.calculate_uv(&intersection_point),
+            front_face,
+            object_id: 0,
+            material: object.get_material(),
+        }
+    }
 }
@@ -1,24 +1,78 @@
 extern crate console_error_panic_hook;
 
+mod aabb;
+mod bsdf;
+mod camera;
+mod capsule;
+mod conductor;
+mod cone;
+mod csg;
 mod cube;
+mod cylinder;
+mod disk;
+mod heightfield;
+mod instance;
 mod intersection;
 mod material;
+mod medium;
+mod noise;
+mod object;
+mod plane;
+mod progress;
+mod quad;
 mod random;
 mod ray;
+mod scene;
+mod sdf;
+mod shader;
+mod solver;
+mod spectrum;
 mod sphere;
+mod texture;
+mod thin_film;
+mod torus;
+mod transform;
 mod vector;
+mod volume;
 
 use crate::{
+    bsdf::Bsdf,
+    camera::Camera,
+    capsule::Capsule,
+    cone::Cone,
+    csg::Csg,
     cube::Cube,
-    intersection::{Intersectable, Intersection},
+    cylinder::Cylinder,
+    disk::Disk,
+    heightfield::Heightfield,
+    instance::Instance,
+    material::Material,
+    medium::{sample_henyey_greenstein, Medium},
+    object::{ObjectId, ObjectInfo, SceneObject},
+    plane::Plane,
+    progress::{JsProgressReporter, Progress},
+    quad::Quad,
     random::Random,
-    ray::Ray,
+    ray::{Ray, RayCone},
+    scene::{RaycastHit, Scene},
+    sdf::Sdf,
+    shader::{Shader, ShaderId},
+    spectrum::Wavelengths,
     sphere::Sphere,
+    texture::{Texture, TextureId},
+    torus::Torus,
     vector::Vector,
+    volume::{track_media, Volume, VolumeEvent, VolumeId},
 };
 
-use std::{cell::RefCell, rc::Rc};
+pub use crate::progress::ProgressReporter;
 
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use js_sys::{Date, Function};
 use wasm_bindgen::{prelude::*, Clamped};
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData};
 
@@ -39,6 +93,9 @@ pub fn console_log(s: &str) {
     log(s);
 }
 
+// Most times a ray can scatter in media between two surfaces before it is given up
+const MAX_SCATTERINGS: u32 = 256;
+
 // Rust Settings struct
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
@@ -46,6 +103,7 @@ pub struct Settings {
     max_reflection_depth: u32,
     num_samples: u32,
     num_frames: u32,
+    pub spectral: bool, // Trace every path at a few wavelengths instead of red, green and blue
 }
 
 #[wasm_bindgen]
@@ -56,49 +114,34 @@ impl Settings {
             max_reflection_depth,
             num_samples,
             num_frames,
+            spectral: false,
         }
     }
 }
 
-// Rust Scene struct
-#[wasm_bindgen]
-#[derive(Debug, Clone, Default)]
-pub struct Scene {
-    spheres: Vec<Sphere>,
-    cubes: Vec<Cube>,
-}
-
-#[wasm_bindgen]
-impl Scene {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Scene {
-        init_panic_hook();
-
-        Scene {
-            spheres: Vec::new(),
-            cubes: Vec::new(),
-        }
-    }
-
-    pub fn add_sphere(&mut self, sphere: Sphere) {
-        self.spheres.push(sphere);
-    }
-
-    pub fn add_cube(&mut self, cube: Cube) {
-        self.cubes.push(cube);
-    }
-}
-
 // Rust Renderer struct
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct Renderer {
     canvas: HtmlCanvasElement,
-    scene: Scene,
+    scene: Rc<RefCell<Scene>>,
     settings: Settings,
     cumulative_image_data: Vec<u32>,
     current_frame: u32,
     random: Random,
+    spectral_volumes: Vec<Volume>, // The volumes at the wavelengths of the path being traced in spectral mode
+    progress_reporter: Option<Rc<RefCell<dyn ProgressReporter>>>,
+    start_time: Option<f64>,
+    status: Rc<Cell<RenderStatus>>,
+    invalidated: Rc<Cell<bool>>,
+}
+
+// State of the render loop, shared between the Renderer and the loop itself
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RenderStatus {
+    Idle,
+    Running,
+    Finished,
 }
 
 #[wasm_bindgen]
@@ -113,27 +156,329 @@ impl Renderer {
 
         Ok(Renderer {
             canvas: canvas.clone(),
-            scene,
+            scene: Rc::new(RefCell::new(scene)),
             settings,
             cumulative_image_data: vec![0; canvas.width() as usize * canvas.height() as usize * 4],
             current_frame: 0,
             random: Random::new(367380976),
+            spectral_volumes: Vec::new(),
+            progress_reporter: None,
+            start_time: None,
+            status: Rc::new(Cell::new(RenderStatus::Idle)),
+            invalidated: Rc::new(Cell::new(false)),
         })
     }
 
+    // Both callbacks receive a Progress object, the first after every rendered row and the second once the last frame is done
+    pub fn set_progress_callbacks(
+        &mut self,
+        on_progress: Option<Function>,
+        on_complete: Option<Function>,
+    ) {
+        self.progress_reporter = Some(Rc::new(RefCell::new(JsProgressReporter::new(
+            on_progress,
+            on_complete,
+        ))));
+    }
+
     pub fn run(&self) -> Result<(), JsValue> {
+        self.status.set(RenderStatus::Running);
+        self.invalidated.set(false);
+
         Renderer::render_next_frame(Rc::new(RefCell::new(self.clone())))?;
 
         Ok(())
     }
 
+    pub fn add_sphere(&self, sphere: Sphere) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_sphere(sphere);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_cube(&self, cube: Cube) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_cube(cube);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_instance(&self, instance: &Instance) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_instance(instance);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_plane(&self, plane: Plane) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_plane(plane);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_disk(&self, disk: Disk) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_disk(disk);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_quad(&self, quad: Quad) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_quad(quad);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_cylinder(&self, cylinder: Cylinder) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_cylinder(cylinder);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_cone(&self, cone: Cone) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_cone(cone);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_capsule(&self, capsule: Capsule) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_capsule(capsule);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_torus(&self, torus: Torus) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_torus(torus);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_csg(&self, csg: &Csg) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_csg(csg);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_sdf(&self, sdf: &Sdf, material: Material) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_sdf(sdf, material);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn add_heightfield(&self, heightfield: &Heightfield) -> Result<ObjectId, JsValue> {
+        let id = self.scene.borrow_mut().add_heightfield(heightfield);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn update_sphere(&self, id: ObjectId, sphere: Sphere) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_sphere(id, sphere)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_cube(&self, id: ObjectId, cube: Cube) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_cube(id, cube)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_instance(&self, id: ObjectId, instance: &Instance) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_instance(id, instance)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_plane(&self, id: ObjectId, plane: Plane) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_plane(id, plane)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_disk(&self, id: ObjectId, disk: Disk) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_disk(id, disk)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_quad(&self, id: ObjectId, quad: Quad) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_quad(id, quad)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_cylinder(&self, id: ObjectId, cylinder: Cylinder) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_cylinder(id, cylinder)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_cone(&self, id: ObjectId, cone: Cone) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_cone(id, cone)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_capsule(&self, id: ObjectId, capsule: Capsule) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_capsule(id, capsule)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_torus(&self, id: ObjectId, torus: Torus) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_torus(id, torus)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_csg(&self, id: ObjectId, csg: &Csg) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_csg(id, csg)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_sdf(&self, id: ObjectId, sdf: &Sdf, material: Material) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_sdf(id, sdf, material)?;
+
+        self.invalidate()
+    }
+
+    pub fn update_heightfield(
+        &self,
+        id: ObjectId,
+        heightfield: &Heightfield,
+    ) -> Result<(), JsValue> {
+        self.scene
+            .borrow_mut()
+            .update_heightfield(id, heightfield)?;
+
+        self.invalidate()
+    }
+
+    pub fn add_texture(&self, texture: &Texture) -> TextureId {
+        self.scene.borrow_mut().add_texture(texture)
+    }
+
+    pub fn update_texture(&self, id: TextureId, texture: &Texture) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_texture(id, texture)?;
+
+        self.invalidate()
+    }
+
+    pub fn add_shader(&self, shader: &Shader) -> ShaderId {
+        self.scene.borrow_mut().add_shader(shader)
+    }
+
+    pub fn update_shader(&self, id: ShaderId, shader: &Shader) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_shader(id, shader)?;
+
+        self.invalidate()
+    }
+
+    pub fn move_object(&self, id: ObjectId, center: Vector) -> Result<(), JsValue> {
+        self.edit_object(id, |object| object.shape.set_center(center))
+    }
+
+    pub fn set_material(&self, id: ObjectId, material: Material) -> Result<(), JsValue> {
+        self.edit_object(id, |object| object.shape.set_material(material))
+    }
+
+    pub fn remove_object(&self, id: ObjectId) -> Result<(), JsValue> {
+        self.scene.borrow_mut().remove(id)?;
+
+        self.invalidate()
+    }
+
+    pub fn list_objects(&self) -> Vec<ObjectInfo> {
+        self.scene.borrow().list()
+    }
+
+    // Find the object under a point on the canvas
+    pub fn pick(&self, x: f64, y: f64) -> Option<RaycastHit> {
+        let scene = self.scene.borrow();
+        let ray = scene.camera.get_ray(
+            x,
+            y,
+            self.canvas.width() as f64,
+            self.canvas.height() as f64,
+        );
+
+        scene.raycast(ray.origin, ray.direction)
+    }
+
+    // Snapshot of the scene that is currently being rendered
+    pub fn scene(&self) -> Scene {
+        self.scene.borrow().clone()
+    }
+
+    // Replace the whole scene, for example with an edited snapshot
+    pub fn set_scene(&self, scene: Scene) -> Result<(), JsValue> {
+        *self.scene.borrow_mut() = scene;
+
+        self.invalidate()
+    }
+
+    pub fn add_volume(&self, volume: &Volume) -> Result<VolumeId, JsValue> {
+        let id = self.scene.borrow_mut().add_volume(volume);
+        self.invalidate()?;
+
+        Ok(id)
+    }
+
+    pub fn update_volume(&self, id: VolumeId, volume: &Volume) -> Result<(), JsValue> {
+        self.scene.borrow_mut().update_volume(id, volume)?;
+
+        self.invalidate()
+    }
+
+    pub fn set_fog(&self, fog: &Medium) -> Result<(), JsValue> {
+        self.scene.borrow_mut().set_fog(fog);
+
+        self.invalidate()
+    }
+
+    pub fn clear_fog(&self) -> Result<(), JsValue> {
+        self.scene.borrow_mut().clear_fog();
+
+        self.invalidate()
+    }
+
+    pub fn set_camera(&self, camera: Camera) -> Result<(), JsValue> {
+        self.scene.borrow_mut().set_camera(camera);
+
+        self.invalidate()
+    }
+
     fn render_next_frame(self_rc: Rc<RefCell<Renderer>>) -> Result<(), JsValue> {
         let closure: Closure<dyn FnMut()> = Closure::wrap(Box::new(move || {
             let mut renderer = self_rc.borrow_mut();
+
+            // Throw away the accumulated frames if the scene changed since the last frame
+            if renderer.invalidated.replace(false) {
+                renderer.reset_accumulation();
+            }
+
             if renderer.current_frame < renderer.settings.num_frames {
                 renderer.render_frame().unwrap();
                 renderer.current_frame += 1;
+
+                if renderer.current_frame == renderer.settings.num_frames {
+                    renderer.report_complete();
+                }
+
                 Renderer::render_next_frame(self_rc.clone()).unwrap();
+            } else {
+                renderer.status.set(RenderStatus::Finished);
             }
         }));
 
@@ -146,6 +491,10 @@ impl Renderer {
     }
 
     fn render_frame(&mut self) -> Result<(), JsValue> {
+        if self.start_time.is_none() {
+            self.start_time = Some(Date::now());
+        }
+
         // Get canvas and context
         let context = self
             .canvas
@@ -172,16 +521,7 @@ impl Renderer {
                 data[i + 3] = 255; // Alpha channel
             }
 
-            let bar_length: f32 = 50.0;
-            let fraction: f32 = y as f32 / self.canvas.height() as f32;
-            let filled_length: usize = (bar_length * fraction) as usize;
-            let bar: String = format!(
-                "[{}{}] {:.2}%",
-                "#".repeat(filled_length),
-                "-".repeat(bar_length as usize - filled_length),
-                fraction * 100.0
-            );
-            console_log(&bar);
+            self.report_progress((y + 1) as f64 / self.canvas.height() as f64);
         }
 
         // Update cumulative_image_data
@@ -208,6 +548,38 @@ impl Renderer {
         Ok(())
     }
 
+    fn reset_accumulation(&mut self) {
+        self.cumulative_image_data.fill(0);
+        self.current_frame = 0;
+        self.start_time = None;
+    }
+
+    fn current_progress(&self, frame_fraction: f64) -> Progress {
+        let elapsed_time = (Date::now() - self.start_time.unwrap_or_else(Date::now)) / 1000.0;
+        let fraction =
+            (self.current_frame as f64 + frame_fraction) / self.settings.num_frames as f64;
+
+        Progress::new(
+            fraction,
+            self.current_frame * self.settings.num_samples,
+            elapsed_time,
+        )
+    }
+
+    fn report_progress(&self, frame_fraction: f64) {
+        if let Some(reporter) = &self.progress_reporter {
+            let progress = self.current_progress(frame_fraction);
+            reporter.borrow_mut().on_progress(&progress);
+        }
+    }
+
+    fn report_complete(&self) {
+        if let Some(reporter) = &self.progress_reporter {
+            let progress = self.current_progress(0.0);
+            reporter.borrow_mut().on_complete(&progress);
+        }
+    }
+
     fn per_pixel(&mut self, x: f64, y: f64) -> Vector {
         // Initialize the accumlateColor Vector
         let mut accumulated_color = Vector::default();
@@ -221,30 +593,56 @@ impl Renderer {
             let sample_x: f64 = x + (sample as f64 + jitter_x) / self.settings.num_samples as f64;
             let sample_y: f64 = y + (sample as f64 + jitter_y) / self.settings.num_samples as f64;
 
-            // Create a ray from the camera to the current pixel
-            let aspect_ratio = self.canvas.width() as f64 / self.canvas.height() as f64;
-            let ray_origin = Vector {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
+            // Create a ray from the camera to the current pixel, as wide as the pixel
+            let camera = self.scene.borrow().camera;
+            let mut ray = camera.get_ray(
+                sample_x,
+                sample_y,
+                self.canvas.width() as f64,
+                self.canvas.height() as f64,
+            );
+            let cone = RayCone {
+                width: 0.0,
+                spread: camera.pixel_spread(self.canvas.width() as f64),
             };
-            let ray_direction = Vector {
-                x: (sample_x / self.canvas.width() as f64) * 2.0 - 1.0,
-                y: ((sample_y / self.canvas.height() as f64) * 2.0 - 1.0) / aspect_ratio,
-                z: -1.0,
+
+            // In spectral mode the path carries its own wavelengths instead of red, green and blue
+            let wavelengths = self
+                .settings
+                .spectral
+                .then(|| Wavelengths::sample(self.random.random_value()));
+
+            // The fog and the volumes are turned into spectra once for the whole path
+            let scene = self.scene.borrow();
+            let fog = match &wavelengths {
+                Some(wavelengths) => scene.fog.map(|fog| fog.spectral(wavelengths)),
+                None => scene.fog,
             };
-            let mut ray = Ray::new(ray_origin, ray_direction);
+            if let Some(wavelengths) = &wavelengths {
+                self.spectral_volumes.clear();
+                self.spectral_volumes.extend(
+                    scene
+                        .volumes
+                        .iter()
+                        .map(|volume| volume.spectral(wavelengths)),
+                );
+            }
+            drop(scene);
 
-            // Trace the ray to get the color
+            // Trace the ray to get the color, the camera is in the fog if there is any
             let color = self.trace_ray(
                 &mut ray,
+                cone,
                 self.settings.max_reflection_depth,
                 Vector {
                     x: 1.0,
                     y: 1.0,
                     z: 1.0,
                 },
+                fog,
+                wavelengths,
             );
+            let color = wavelengths.map_or(color, |wavelengths| wavelengths.rgb(&color));
 
             // Accumulate the color
             accumulated_color += color;
@@ -253,8 +651,16 @@ impl Renderer {
         accumulated_color / self.settings.num_samples as f64
     }
 
-    fn trace_ray(&mut self, ray: &mut Ray, depth: u32, mut ray_color: Vector) -> Vector {
-        if depth <= 0 {
+    fn trace_ray(
+        &mut self,
+        ray: &mut Ray,
+        cone: RayCone,
+        depth: u32,
+        mut ray_color: Vector,
+        medium: Option<Medium>,
+        mut wavelengths: Option<Wavelengths>,
+    ) -> Vector {
+        if depth == 0 {
             return Vector::default();
         }
 
@@ -262,79 +668,171 @@ impl Renderer {
 
         let random = &mut self.random;
 
-        let mut closest_intersection: Option<Rc<Intersection>> = None;
-
-        for sphere in &self.scene.spheres {
-            if let Some(intersection_result) = sphere.intersect(ray) {
-                if closest_intersection.is_none() {
-                    closest_intersection = Some(intersection_result.into());
-                } else if intersection_result.t
-                    < closest_intersection.as_ref().map_or(f64::INFINITY, |x| x.t)
-                {
-                    closest_intersection = Some(intersection_result.into());
-                }
-            }
-        }
+        let scene = self.scene.borrow();
+        let mut cone = cone;
+        let mut medium = medium;
+        let scene_fog = match &wavelengths {
+            Some(wavelengths) => scene.fog.map(|fog| fog.spectral(wavelengths)),
+            None => scene.fog,
+        };
+        let volumes = match wavelengths {
+            Some(_) => self.spectral_volumes.as_slice(),
+            None => scene.volumes.as_slice(),
+        };
 
-        for cube in &self.scene.cubes {
-            if let Some(intersection_result) = cube.intersect(ray) {
-                if closest_intersection.is_none() {
-                    closest_intersection = Some(intersection_result.into());
-                } else if intersection_result.t
-                    < closest_intersection.as_ref().map_or(f64::INFINITY, |x| x.t)
-                {
-                    closest_intersection = Some(intersection_result.into());
+        // The ray can scatter in the volumes and the medium it is going through before it gets to the surface,
+        // rays that leave the scene are not fogged so the background can still be seen. Scattering does not
+        // count as a bounce, so random walks through dense media can take many steps
+        let mut scatterings = 0;
+        let closest_intersection = loop {
+            let closest_intersection = scene.closest_intersection(ray);
+            let speed = ray.direction.magnitude();
+            let hit_distance = closest_intersection
+                .as_ref()
+                .map_or(f64::INFINITY, |intersection| intersection.t * speed);
+            let fog = medium.filter(|_| closest_intersection.is_some());
+            let track = track_media(volumes, fog.as_ref(), ray, hit_distance, random);
+            incoming_light += ray_color * track.emitted;
+            ray_color *= track.weight;
+
+            match track.event {
+                VolumeEvent::Pass => break closest_intersection,
+                VolumeEvent::Absorb => return incoming_light,
+                VolumeEvent::Scatter {
+                    distance,
+                    anisotropy,
+                } => {
+                    scatterings += 1;
+                    if scatterings > MAX_SCATTERINGS {
+                        return incoming_light;
+                    }
+
+                    // A scatter within the error bound of the surface point could hit the surface again
+                    // right away, so it is kept back from the surface by that bound
+                    let margin = closest_intersection.as_ref().map_or(0.0, |intersection| {
+                        ray.point_error(intersection.t).magnitude()
+                    });
+                    let distance = distance.min(hit_distance - margin).max(0.0);
+
+                    cone = cone.at(distance);
+                    *ray = Ray::new(
+                        ray.point_at_parameter(distance / speed),
+                        sample_henyey_greenstein(&ray.direction, anisotropy, random),
+                    );
                 }
             }
-        }
+        };
 
         match closest_intersection {
             Some(intersection) => {
                 let intersection_point = intersection.intersection_point;
 
-                // Get the normal on the Sphere
-                let normal = intersection
-                    .intersection_object
-                    .calculate_normal(&intersection_point);
-
-                // Update the origin and direction of the ray for the next iteration
-                ray.origin = intersection_point;
-                ray.direction = if intersection.intersection_object.get_material().roughness <= 0.1 {
-                    ray.direction.reflect(&normal)
-                } else {
-                    random.random_hemisphere_direction(&normal)
+                // Widen the cone up to the hit, the bounced ray keeps spreading at the same rate
+                cone = cone.at(intersection.t * ray.direction.magnitude());
+                let material = scene.surface_material(&intersection, &ray.direction, cone.width);
+                let material = match &wavelengths {
+                    Some(wavelengths) => material.spectral(wavelengths),
+                    None => material,
                 };
 
+                // The normals face against the ray so both sides of planes, disks and quads are lit
+                let normal = scene.shading_normal(&intersection, &material, cone.width);
+                let geometric_normal = intersection.geometric_normal;
+
                 // Calculate the incoming light
-                let emission_color = intersection
-                    .intersection_object
-                    .get_material()
-                    .emission_color;
-                let emission_power = intersection
-                    .intersection_object
-                    .get_material()
-                    .emission_power;
-                let emitted_light = emission_color * emission_power;
+                let emitted_light = material.emitted();
                 let emission = emitted_light * ray_color;
                 incoming_light += emission;
 
-                ray_color *= intersection.intersection_object.get_material().color;
+                if material.emission_power > 0.0 {
+                    return incoming_light;
+                }
 
-                if intersection
-                    .intersection_object
-                    .get_material()
-                    .emission_power
-                    > 0.0
-                {
+                // Pick the next direction from the lobes of the material, the path ends if none is found
+                let bsdf = Bsdf::new(
+                    &material,
+                    &normal,
+                    &intersection.dpdu,
+                    intersection.front_face,
+                );
+                let Some(sample) = bsdf.sample(&ray.direction, random) else {
                     return incoming_light;
+                };
+
+                // Directions from the shading normal can end up on the wrong side of the surface, mirror them back
+                let transmitted = sample.direction.dot(&normal) < 0.0;
+
+                // Going through a surface that bounds a medium enters it or leaves it for the fog
+                let interior = material.interior_medium();
+                if transmitted && interior.is_some() {
+                    medium = if intersection.front_face {
+                        interior
+                    } else {
+                        scene_fog
+                    };
+                }
+
+                // Dispersion bends every wavelength differently, so only the hero wavelength goes on
+                if let Some(wavelengths) = &mut wavelengths {
+                    if transmitted && material.abbe_number > 0.0 {
+                        wavelengths.terminate_secondary(&mut ray_color);
+                    }
                 }
+                let direction = if (sample.direction.dot(&geometric_normal) < 0.0) != transmitted {
+                    sample.direction.reflect(&geometric_normal)
+                } else {
+                    sample.direction
+                };
+
+                // Update the ray for the next iteration, starting just off the surface so it does not hit it again
+                *ray = Ray::spawn(
+                    &intersection_point,
+                    &ray.point_error(intersection.t),
+                    &geometric_normal,
+                    direction,
+                );
+
+                ray_color *= sample.weight;
             }
             None => {
                 let background_color = ray.get_background_color();
-                return ray_color * background_color;
+                let background_color = wavelengths.map_or(background_color, |wavelengths| {
+                    wavelengths.upsample(&background_color)
+                });
+                return incoming_light + ray_color * background_color;
             }
         };
 
-        self.trace_ray(ray, depth + 1, ray_color)
+        drop(scene);
+        incoming_light + self.trace_ray(ray, cone, depth - 1, ray_color, medium, wavelengths)
+    }
+}
+
+impl Renderer {
+    // Native counterpart of set_progress_callbacks
+    pub fn set_progress_reporter(&mut self, reporter: Rc<RefCell<dyn ProgressReporter>>) {
+        self.progress_reporter = Some(reporter);
+    }
+
+    // Restart the convergence of the image after the scene has been changed
+    fn invalidate(&self) -> Result<(), JsValue> {
+        match self.status.get() {
+            RenderStatus::Idle => Ok(()),
+            RenderStatus::Running => {
+                self.invalidated.set(true);
+                Ok(())
+            }
+            RenderStatus::Finished => self.run(),
+        }
+    }
+
+    fn edit_object(
+        &self,
+        id: ObjectId,
+        edit: impl FnOnce(&mut SceneObject),
+    ) -> Result<(), JsValue> {
+        edit(self.scene.borrow_mut().object_mut(id)?);
+
+        self.invalidate()
     }
 }
@@ -1,21 +1,128 @@
 use wasm_bindgen::prelude::*;
 
-use crate::{init_panic_hook, vector::Vector};
+use crate::{
+    conductor::Conductor,
+    init_panic_hook,
+    medium::Medium,
+    shader::ShaderId,
+    spectrum::{blackbody_rgb, dispersed_ior, Wavelengths},
+    texture::TextureId,
+    vector::Vector,
+};
 
 // Rust Material struct
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 #[allow(dead_code)]
 pub struct Material {
-    pub color: Vector,  // RGB color/albedo of the material
-    pub roughness: f64, // Reflection coefficient between 0 and 1, roughness zero means just reflections
+    pub color: Vector,  // RGB base color/albedo of the material
+    pub roughness: f64, // Between 0 for sharp reflections and 1 for rough ones
     pub emission_color: Vector,
     pub emission_power: f64,
-    // pub metallic: f64,       // Defines the splecularness of the Material
+    pub emission_temperature: f64, // Kelvin of a black body whose light replaces the emission color, 0 for none
+    pub metallic: f64, // Between 0 for dielectrics and 1 for metals, which reflect in their base color
+    pub specular: f64, // Reflectance of dielectrics, 0.5 is 4% like most plastics and glass
+    pub specular_tint: f64, // How much the reflections of dielectrics take the hue of the base color
+    pub anisotropy: f64,    // Stretches the reflections along the texture coordinate u
+    pub sheen: f64,         // Soft reflections at grazing angles, for cloth
+    pub clearcoat: f64,     // Strength of a second, colorless reflective layer
+    pub clearcoat_roughness: f64,
+    pub clearcoat_ior: f64,
+    pub clearcoat_color: Vector, // Color of the coat seen head-on, the layers below are seen through it
+    pub transmission: f64, // Between 0 for opaque and 1 for glass, which lets light through in its base color
+    pub ior: f64,          // Index of refraction of the transmission
+    pub abbe_number: f64, // How little the index of refraction changes with the wavelength in spectral mode, 0 for not at all
+    pub thin_film_thickness: f64, // Nanometers of a film over the reflections that gives iridescent colors, 0 for none
+    pub thin_film_ior: f64,
+    pub subsurface: f64, // Between 0 and 1, how much of the light goes under the surface and scatters before it comes out
+    pub subsurface_radius: Vector, // Mean free path under the surface for red, green and blue, in scene units
+    pub(crate) conductor: Option<Conductor>, // Complex index of refraction of a metal
+    pub(crate) medium: Option<Medium>, // Fills the inside of the object
+    pub(crate) color_texture: Option<TextureId>, // Textures in the scene that multiply the parameters
+    pub(crate) roughness_texture: Option<TextureId>,
+    pub(crate) emission_texture: Option<TextureId>,
+    pub(crate) normal_map: Option<TextureId>, // Normals in the tangent space of the surface
+    pub(crate) bump_map: Option<TextureId>,   // Heights from the red channel
+    pub(crate) bump_strength: f64,
+    pub(crate) shader: Option<ShaderId>, // Shader in the scene whose outputs replace the parameters
+    pub(crate) shader_normal: Option<Vector>, // Normal in tangent space the shader gave at a hit
+    pub(crate) wavelengths: Option<Wavelengths>, // Wavelengths the colors stand for in spectral mode
+}
+
+impl Material {
+    // Method to get the light the material gives off
+    pub(crate) fn emitted(&self) -> Vector {
+        let color = if self.emission_temperature > 0.0 {
+            blackbody_rgb(self.emission_temperature)
+        } else {
+            self.emission_color
+        };
+
+        color * self.emission_power
+    }
+
+    // Method to get the material with its colors at the wavelengths of a path in spectral mode, the index of
+    // refraction is the one of the hero wavelength
+    pub(crate) fn spectral(&self, wavelengths: &Wavelengths) -> Material {
+        let emission_color = if self.emission_temperature > 0.0 {
+            wavelengths.blackbody(self.emission_temperature)
+        } else {
+            wavelengths.upsample(&self.emission_color)
+        };
+
+        Material {
+            color: wavelengths.upsample(&self.color),
+            emission_color,
+            emission_temperature: 0.0,
+            clearcoat_color: wavelengths.upsample(&self.clearcoat_color),
+            ior: dispersed_ior(self.ior, self.abbe_number, wavelengths.hero()),
+            subsurface_radius: wavelengths.upsample(&self.subsurface_radius),
+            conductor: self.conductor.map(|conductor| Conductor {
+                eta: wavelengths.interpolate(&conductor.eta),
+                k: wavelengths.interpolate(&conductor.k),
+            }),
+            medium: self.medium.map(|medium| medium.spectral(wavelengths)),
+            wavelengths: Some(*wavelengths),
+            ..*self
+        }
+    }
+
+    // Method to get the medium inside the object, the subsurface scattering is used if no medium was set
+    pub(crate) fn interior_medium(&self) -> Option<Medium> {
+        if self.medium.is_some() || self.subsurface <= 0.0 {
+            return self.medium;
+        }
+
+        // Single scattering albedo that gives the base color after many bounces, from Chiang et al.
+        let albedo = |color: f64| {
+            let color = color.clamp(0.0, 0.999);
+            let s = 4.09712 + 4.20863 * color
+                - (9.59217 + 41.6808 * color + 17.7126 * color * color).sqrt();
+            1.0 - s * s
+        };
+        let extinction = |radius: f64| 1.0 / radius.max(1e-6);
+        let extinction = Vector::new(
+            extinction(self.subsurface_radius.x),
+            extinction(self.subsurface_radius.y),
+            extinction(self.subsurface_radius.z),
+        );
+        let scattering = Vector::new(
+            albedo(self.color.x),
+            albedo(self.color.y),
+            albedo(self.color.z),
+        ) * extinction;
+
+        Some(Medium {
+            absorption: extinction - scattering,
+            scattering,
+            anisotropy: 0.0,
+        })
+    }
 }
 
 #[wasm_bindgen]
 impl Material {
+    // A roughness of 0.1 or less gives a mirror, like before the material had the principled parameters
     #[wasm_bindgen(constructor)]
     pub fn new(
         color: Vector,
@@ -23,13 +130,131 @@ impl Material {
         emission_color: Vector,
         emission_power: f64,
     ) -> Material {
+        Material {
+            emission_color,
+            emission_power,
+            metallic: if roughness <= 0.1 { 1.0 } else { 0.0 },
+            ..Material::principled(color, roughness)
+        }
+    }
+
+    // Plain dielectric without emission whose principled parameters are all set directly
+    pub fn principled(color: Vector, roughness: f64) -> Material {
         init_panic_hook();
 
         Material {
             color,
             roughness,
-            emission_color,
-            emission_power,
+            emission_color: Vector::default(),
+            emission_power: 0.0,
+            emission_temperature: 0.0,
+            metallic: 0.0,
+            specular: 0.5,
+            specular_tint: 0.0,
+            anisotropy: 0.0,
+            sheen: 0.0,
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.03,
+            clearcoat_ior: 1.5,
+            clearcoat_color: Vector::new(1.0, 1.0, 1.0),
+            transmission: 0.0,
+            ior: 1.5,
+            abbe_number: 0.0,
+            thin_film_thickness: 0.0,
+            thin_film_ior: 1.33,
+            subsurface: 0.0,
+            subsurface_radius: Vector::new(1.0, 0.2, 0.1),
+            conductor: None,
+            medium: None,
+            color_texture: None,
+            roughness_texture: None,
+            emission_texture: None,
+            normal_map: None,
+            bump_map: None,
+            bump_strength: 0.0,
+            shader: None,
+            shader_normal: None,
+            wavelengths: None,
+        }
+    }
+
+    // Metal with the complex index of refraction per channel, eta is the real part and k how much light is absorbed
+    pub fn with_conductor(&self, eta: Vector, k: Vector) -> Material {
+        Material {
+            metallic: 1.0,
+            conductor: Some(Conductor { eta, k }),
+            ..*self
+        }
+    }
+
+    // Metal from the built-in table, like gold, silver, copper or aluminum
+    pub fn with_metal(&self, name: &str) -> Result<Material, JsValue> {
+        let conductor = Conductor::named(name).ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "Unknown metal {name}, expected one of {}",
+                Conductor::names().join(", ")
+            ))
+        })?;
+
+        Ok(Material {
+            metallic: 1.0,
+            conductor: Some(conductor),
+            ..*self
+        })
+    }
+
+    // The object has to be closed, light gets into the medium through the transmission of the material
+    pub fn with_medium(&self, medium: &Medium) -> Material {
+        Material {
+            medium: Some(*medium),
+            ..*self
+        }
+    }
+
+    pub fn with_color_texture(&self, texture: TextureId) -> Material {
+        Material {
+            color_texture: Some(texture),
+            ..*self
+        }
+    }
+
+    // The roughness is multiplied by the red channel of the texture
+    pub fn with_roughness_texture(&self, texture: TextureId) -> Material {
+        Material {
+            roughness_texture: Some(texture),
+            ..*self
+        }
+    }
+
+    pub fn with_emission_texture(&self, texture: TextureId) -> Material {
+        Material {
+            emission_texture: Some(texture),
+            ..*self
+        }
+    }
+
+    // Red, green and blue of the texture are the normal along u, along v and out of the surface
+    pub fn with_normal_map(&self, texture: TextureId) -> Material {
+        Material {
+            normal_map: Some(texture),
+            ..*self
+        }
+    }
+
+    // The strength is how far the surface moves out for a height of 1
+    pub fn with_bump_map(&self, texture: TextureId, strength: f64) -> Material {
+        Material {
+            bump_map: Some(texture),
+            bump_strength: strength,
+            ..*self
+        }
+    }
+
+    // The outputs of the shader replace the parameters after the textures are applied
+    pub fn with_shader(&self, shader: ShaderId) -> Material {
+        Material {
+            shader: Some(shader),
+            ..*self
         }
     }
 }
@@ -0,0 +1,166 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{init_panic_hook, random::Random, spectrum::Wavelengths, vector::Vector};
+
+// Rust Medium struct, a homogeneous volume of particles that absorb and scatter light
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Medium {
+    pub absorption: Vector, // Per unit of distance, for red, green and blue
+    pub scattering: Vector,
+    pub anisotropy: f64, // Henyey-Greenstein g, from -1 scattering back through 0 evenly to 1 forward
+}
+
+impl Medium {
+    // Method to get the medium with its coefficients at the wavelengths of a path in spectral mode
+    pub(crate) fn spectral(&self, wavelengths: &Wavelengths) -> Medium {
+        Medium {
+            absorption: wavelengths.upsample(&self.absorption),
+            scattering: wavelengths.upsample(&self.scattering),
+            ..*self
+        }
+    }
+
+    pub(crate) fn extinction(&self) -> Vector {
+        self.absorption + self.scattering
+    }
+
+    // Method to find where a ray going up to max_distance scatters, if it does, and the weight of the path up to there
+    pub(crate) fn sample_distance(
+        &self,
+        max_distance: f64,
+        random: &mut Random,
+    ) -> (Option<f64>, Vector) {
+        let extinction = self.extinction();
+        let transmittance = |distance: f64| {
+            Vector::new(
+                (-extinction.x * distance).exp(),
+                (-extinction.y * distance).exp(),
+                (-extinction.z * distance).exp(),
+            )
+        };
+
+        // Free flight in one of the channels, the weight accounts for the other two
+        let channel = match (random.random_value() * 3.0) as u32 {
+            0 => extinction.x,
+            1 => extinction.y,
+            _ => extinction.z,
+        };
+        let distance = if channel > 0.0 {
+            -(1.0 - random.random_value()).max(f64::MIN_POSITIVE).ln() / channel
+        } else {
+            f64::INFINITY
+        };
+
+        if distance < max_distance {
+            let tr = transmittance(distance);
+            let pdf = (extinction * tr).dot(&Vector::new(1.0, 1.0, 1.0)) / 3.0;
+            if pdf <= 0.0 {
+                return (None, Vector::default());
+            }
+
+            (Some(distance), self.scattering * tr / pdf)
+        } else {
+            let tr = transmittance(max_distance);
+            let probability = (tr.x + tr.y + tr.z) / 3.0;
+            if probability <= 0.0 {
+                return (None, Vector::default());
+            }
+
+            (None, tr / probability)
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Medium {
+    #[wasm_bindgen(constructor)]
+    pub fn new(absorption: Vector, scattering: Vector, anisotropy: f64) -> Medium {
+        init_panic_hook();
+
+        Medium {
+            absorption,
+            scattering,
+            anisotropy,
+        }
+    }
+}
+
+// Method to pick the new direction of a ray going in the direction that scatters, with the Henyey-Greenstein phase function
+pub(crate) fn sample_henyey_greenstein(
+    direction: &Vector,
+    anisotropy: f64,
+    random: &mut Random,
+) -> Vector {
+    let g = anisotropy.clamp(-0.99, 0.99);
+    let u = random.random_value();
+
+    // Inverse of the cumulative distribution of the angle to the direction
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * u
+    } else {
+        let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+        (1.0 + g * g - s * s) / (2.0 * g)
+    }
+    .clamp(-1.0, 1.0);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = 2.0 * PI * random.random_value();
+
+    let forward = direction.normalize();
+    let (tangent, bitangent) = forward.orthonormal_basis();
+    tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + forward * cos_theta
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mean weight of the paths that get through, which estimates the transmittance over the distance
+    fn mean_transmittance(medium: &Medium, distance: f64) -> Vector {
+        let count = 200_000;
+        let mut random = Random::new(3);
+        let mut total = Vector::default();
+        for _ in 0..count {
+            if let (None, weight) = medium.sample_distance(distance, &mut random) {
+                total += weight;
+            }
+        }
+
+        total / count as f64
+    }
+
+    fn assert_beer_lambert(medium: &Medium, distance: f64) {
+        let estimate = mean_transmittance(medium, distance);
+        let extinction = medium.extinction();
+        for (estimate, extinction) in [
+            (estimate.x, extinction.x),
+            (estimate.y, extinction.y),
+            (estimate.z, extinction.z),
+        ] {
+            let expected = (-extinction * distance).exp();
+            assert!(
+                (estimate - expected).abs() < 0.01 * expected.max(0.1),
+                "expected {expected} but got {estimate}"
+            );
+        }
+    }
+
+    #[test]
+    fn transmittance_of_a_grey_medium() {
+        let medium = Medium::new(Vector::new(0.2, 0.2, 0.2), Vector::new(0.3, 0.3, 0.3), 0.0);
+
+        assert_beer_lambert(&medium, 1.0);
+        assert_beer_lambert(&medium, 4.0);
+    }
+
+    #[test]
+    fn transmittance_of_a_colored_medium() {
+        // Each channel keeps its own transmittance even though the distance is sampled in one of them
+        let medium = Medium::new(Vector::new(0.05, 0.4, 1.0), Vector::new(0.1, 0.2, 0.0), 0.5);
+
+        assert_beer_lambert(&medium, 1.0);
+        assert_beer_lambert(&medium, 3.0);
+    }
+}
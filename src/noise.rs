@@ -0,0 +1,136 @@
+use crate::vector::Vector;
+
+// Method to scramble the coordinates of a lattice point into a pseudo-random number, the same every time
+fn hash(x: i64, y: i64, z: i64, seed: u64) -> u64 {
+    let mut h = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (z as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+    // Finalizer of SplitMix64
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+// Method to get a number between 0 and 1 from a hash
+fn unit(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Dot product of the offset with one of the 12 gradients that point to the edges of a cube
+fn gradient(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    match hash % 12 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x + z,
+        5 => -x + z,
+        6 => x - z,
+        7 => -x - z,
+        8 => y + z,
+        9 => -y + z,
+        10 => y - z,
+        _ => -y - z,
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+// Improved Perlin noise, between about -1 and 1
+pub fn perlin(point: &Vector) -> f64 {
+    let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+    let (x, y, z) = (point.x - x0, point.y - y0, point.z - z0);
+    let (ix, iy, iz) = (x0 as i64, y0 as i64, z0 as i64);
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        gradient(
+            hash(ix + dx, iy + dy, iz + dz, 0),
+            x - dx as f64,
+            y - dy as f64,
+            z - dz as f64,
+        )
+    };
+
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+    lerp(
+        lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        ),
+        lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        ),
+        w,
+    )
+}
+
+// Fractal Brownian motion, octaves of noise with double the frequency and half the amplitude
+pub fn fbm(point: &Vector, octaves: u32) -> f64 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut point = *point;
+
+    for _ in 0..octaves.max(1) {
+        sum += perlin(&point) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        point = point * 2.0;
+    }
+
+    sum / total_amplitude
+}
+
+// Like fbm but with the absolute value of every octave, which gives sharp creases
+pub fn turbulence(point: &Vector, octaves: u32) -> f64 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    let mut point = *point;
+
+    for _ in 0..octaves.max(1) {
+        sum += perlin(&point).abs() * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+        point = point * 2.0;
+    }
+
+    sum / total_amplitude
+}
+
+// Cellular noise, the distance to the closest of the points scattered one in every unit cell
+pub fn worley(point: &Vector) -> f64 {
+    let (ix, iy, iz) = (
+        point.x.floor() as i64,
+        point.y.floor() as i64,
+        point.z.floor() as i64,
+    );
+    let mut closest = f64::INFINITY;
+
+    for dz in -1..=1 {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (cx, cy, cz) = (ix + dx, iy + dy, iz + dz);
+                let feature = Vector::new(
+                    cx as f64 + unit(hash(cx, cy, cz, 1)),
+                    cy as f64 + unit(hash(cx, cy, cz, 2)),
+                    cz as f64 + unit(hash(cx, cy, cz, 3)),
+                );
+                closest = closest.min((feature - *point).magnitude());
+            }
+        }
+    }
+
+    closest
+}
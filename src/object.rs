@@ -0,0 +1,368 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    capsule::Capsule,
+    cone::Cone,
+    csg::{spans_from_crossings, CsgNode, Solid, Spans},
+    cube::Cube,
+    cylinder::Cylinder,
+    disk::Disk,
+    heightfield::Heightfield,
+    instance::Instance,
+    intersection::{Intersectable, Intersection},
+    material::Material,
+    plane::Plane,
+    quad::Quad,
+    ray::Ray,
+    sdf::SdfObject,
+    sphere::Sphere,
+    torus::Torus,
+    vector::Vector,
+};
+
+// Handle of an object in the scene, stays the same for the lifetime of the object
+pub type ObjectId = u32;
+
+// Rust Shape enum, one variant for every kind of object that can be in the scene
+#[derive(Debug, Clone)]
+pub(crate) enum Shape {
+    Sphere(Sphere),
+    Cube(Cube),
+    Instance(Instance),
+    Plane(Plane),
+    Disk(Disk),
+    Quad(Quad),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Capsule(Capsule),
+    Torus(Torus),
+    Csg(CsgNode),
+    Sdf(SdfObject),
+    Heightfield(Heightfield),
+}
+
+impl Shape {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Shape::Sphere(_) => "sphere",
+            Shape::Cube(_) => "cube",
+            Shape::Instance(_) => "instance",
+            Shape::Plane(_) => "plane",
+            Shape::Disk(_) => "disk",
+            Shape::Quad(_) => "quad",
+            Shape::Cylinder(_) => "cylinder",
+            Shape::Cone(_) => "cone",
+            Shape::Capsule(_) => "capsule",
+            Shape::Torus(_) => "torus",
+            Shape::Csg(_) => "csg",
+            Shape::Sdf(_) => "sdf",
+            Shape::Heightfield(_) => "heightfield",
+        }
+    }
+
+    // Point that is moved by set_center
+    pub fn center(&self) -> Vector {
+        match self {
+            Shape::Sphere(sphere) => sphere.center,
+            Shape::Cube(cube) => cube.center,
+            Shape::Instance(instance) => instance.transform().translation(),
+            Shape::Plane(plane) => plane.point,
+            Shape::Disk(disk) => disk.center,
+            Shape::Quad(quad) => quad.corner + (quad.edge_u + quad.edge_v) / 2.0,
+            Shape::Cylinder(cylinder) => cylinder.center,
+            Shape::Cone(cone) => cone.base,
+            Shape::Capsule(capsule) => (capsule.start + capsule.end) / 2.0,
+            Shape::Torus(torus) => torus.center,
+            Shape::Csg(csg) => csg.left.center(),
+            Shape::Sdf(sdf) => sdf.center(),
+            Shape::Heightfield(heightfield) => heightfield.center(),
+        }
+    }
+
+    pub fn set_center(&mut self, center: Vector) {
+        let offset = center - self.center();
+
+        match self {
+            Shape::Sphere(sphere) => sphere.center = center,
+            Shape::Cube(cube) => cube.center = center,
+            Shape::Instance(instance) => {
+                instance.set_transform(instance.transform().with_translation(center))
+            }
+            Shape::Plane(plane) => plane.point = center,
+            Shape::Disk(disk) => disk.center = center,
+            Shape::Quad(quad) => quad.corner = center - (quad.edge_u + quad.edge_v) / 2.0,
+            Shape::Cylinder(cylinder) => cylinder.center = center,
+            Shape::Cone(cone) => cone.base = center,
+            Shape::Capsule(capsule) => {
+                let half_segment = (capsule.end - capsule.start) / 2.0;
+                capsule.start = center - half_segment;
+                capsule.end = center + half_segment;
+            }
+            Shape::Torus(torus) => torus.center = center,
+            Shape::Csg(csg) => {
+                // Move both children by the same offset
+                for child in [&mut csg.left, &mut csg.right] {
+                    let child_center = child.center();
+                    Rc::make_mut(child).set_center(child_center + offset);
+                }
+            }
+            Shape::Sdf(sdf) => sdf.set_center(center),
+            Shape::Heightfield(heightfield) => heightfield.set_center(center),
+        }
+    }
+
+    pub fn set_material(&mut self, material: Material) {
+        match self {
+            Shape::Sphere(sphere) => sphere.material = material,
+            Shape::Cube(cube) => cube.material = material,
+            Shape::Instance(instance) => instance.set_material(material),
+            Shape::Plane(plane) => plane.material = material,
+            Shape::Disk(disk) => disk.material = material,
+            Shape::Quad(quad) => quad.material = material,
+            Shape::Cylinder(cylinder) => cylinder.material = material,
+            Shape::Cone(cone) => cone.material = material,
+            Shape::Capsule(capsule) => capsule.material = material,
+            Shape::Torus(torus) => torus.material = material,
+            Shape::Csg(csg) => {
+                for child in [&mut csg.left, &mut csg.right] {
+                    Rc::make_mut(child).set_material(material);
+                }
+            }
+            Shape::Sdf(sdf) => sdf.material = material,
+            Shape::Heightfield(heightfield) => heightfield.set_material(material),
+        }
+    }
+}
+
+impl Shape {
+    // Method to get the spans of the ray inside the shape, None if the shape is not a closed solid
+    pub fn spans(&self, ray: &Ray) -> Option<Spans> {
+        let crossings = match self {
+            Shape::Sphere(sphere) => sphere.crossings(ray),
+            Shape::Cube(cube) => cube.crossings(ray),
+            Shape::Cylinder(cylinder) if cylinder.capped => cylinder.crossings(ray),
+            Shape::Cone(cone) if cone.capped => cone.crossings(ray),
+            Shape::Capsule(capsule) => capsule.crossings(ray),
+            Shape::Torus(torus) => torus.crossings(ray),
+            Shape::Instance(instance) => return instance.spans(ray),
+            Shape::Csg(csg) => return Some(csg.spans(ray)),
+            _ => return None,
+        };
+
+        Some(spans_from_crossings(&crossings))
+    }
+}
+
+impl Intersectable for Shape {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        match self {
+            Shape::Sphere(sphere) => sphere.intersect(ray),
+            Shape::Cube(cube) => cube.intersect(ray),
+            Shape::Instance(instance) => instance.intersect(ray),
+            Shape::Plane(plane) => plane.intersect(ray),
+            Shape::Disk(disk) => disk.intersect(ray),
+            Shape::Quad(quad) => quad.intersect(ray),
+            Shape::Cylinder(cylinder) => cylinder.intersect(ray),
+            Shape::Cone(cone) => cone.intersect(ray),
+            Shape::Capsule(capsule) => capsule.intersect(ray),
+            Shape::Torus(torus) => torus.intersect(ray),
+            Shape::Csg(csg) => csg.intersect(ray),
+            Shape::Sdf(sdf) => sdf.intersect(ray),
+            Shape::Heightfield(heightfield) => heightfield.intersect(ray),
+        }
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        match self {
+            Shape::Sphere(sphere) => sphere.calculate_normal(point),
+            Shape::Cube(cube) => cube.calculate_normal(point),
+            Shape::Instance(instance) => instance.calculate_normal(point),
+            Shape::Plane(plane) => plane.calculate_normal(point),
+            Shape::Disk(disk) => disk.calculate_normal(point),
+            Shape::Quad(quad) => quad.calculate_normal(point),
+            Shape::Cylinder(cylinder) => cylinder.calculate_normal(point),
+            Shape::Cone(cone) => cone.calculate_normal(point),
+            Shape::Capsule(capsule) => capsule.calculate_normal(point),
+            Shape::Torus(torus) => torus.calculate_normal(point),
+            Shape::Csg(csg) => csg.calculate_normal(point),
+            Shape::Sdf(sdf) => sdf.calculate_normal(point),
+            Shape::Heightfield(heightfield) => heightfield.calculate_normal(point),
+        }
+    }
+
+    fn calculate_geometric_normal(&self, point: &Vector) -> Vector {
+        match self {
+            Shape::Sphere(sphere) => sphere.calculate_geometric_normal(point),
+            Shape::Cube(cube) => cube.calculate_geometric_normal(point),
+            Shape::Instance(instance) => instance.calculate_geometric_normal(point),
+            Shape::Plane(plane) => plane.calculate_geometric_normal(point),
+            Shape::Disk(disk) => disk.calculate_geometric_normal(point),
+            Shape::Quad(quad) => quad.calculate_geometric_normal(point),
+            Shape::Cylinder(cylinder) => cylinder.calculate_geometric_normal(point),
+            Shape::Cone(cone) => cone.calculate_geometric_normal(point),
+            Shape::Capsule(capsule) => capsule.calculate_geometric_normal(point),
+            Shape::Torus(torus) => torus.calculate_geometric_normal(point),
+            Shape::Csg(csg) => csg.calculate_geometric_normal(point),
+            Shape::Sdf(sdf) => sdf.calculate_geometric_normal(point),
+            Shape::Heightfield(heightfield) => heightfield.calculate_geometric_normal(point),
+        }
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        match self {
+            Shape::Sphere(sphere) => sphere.calculate_uv(point),
+            Shape::Cube(cube) => cube.calculate_uv(point),
+            Shape::Instance(instance) => instance.calculate_uv(point),
+            Shape::Plane(plane) => plane.calculate_uv(point),
+            Shape::Disk(disk) => disk.calculate_uv(point),
+            Shape::Quad(quad) => quad.calculate_uv(point),
+            Shape::Cylinder(cylinder) => cylinder.calculate_uv(point),
+            Shape::Cone(cone) => cone.calculate_uv(point),
+            Shape::Capsule(capsule) => capsule.calculate_uv(point),
+            Shape::Torus(torus) => torus.calculate_uv(point),
+            Shape::Csg(csg) => csg.calculate_uv(point),
+            Shape::Sdf(sdf) => sdf.calculate_uv(point),
+            Shape::Heightfield(heightfield) => heightfield.calculate_uv(point),
+        }
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        match self {
+            Shape::Sphere(sphere) => sphere.calculate_tangents(point),
+            Shape::Cube(cube) => cube.calculate_tangents(point),
+            Shape::Instance(instance) => instance.calculate_tangents(point),
+            Shape::Plane(plane) => plane.calculate_tangents(point),
+            Shape::Disk(disk) => disk.calculate_tangents(point),
+            Shape::Quad(quad) => quad.calculate_tangents(point),
+            Shape::Cylinder(cylinder) => cylinder.calculate_tangents(point),
+            Shape::Cone(cone) => cone.calculate_tangents(point),
+            Shape::Capsule(capsule) => capsule.calculate_tangents(point),
+            Shape::Torus(torus) => torus.calculate_tangents(point),
+            Shape::Csg(csg) => csg.calculate_tangents(point),
+            Shape::Sdf(sdf) => sdf.calculate_tangents(point),
+            Shape::Heightfield(heightfield) => heightfield.calculate_tangents(point),
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        match self {
+            Shape::Sphere(sphere) => sphere.bounds(),
+            Shape::Cube(cube) => cube.bounds(),
+            Shape::Instance(instance) => instance.bounds(),
+            Shape::Plane(plane) => plane.bounds(),
+            Shape::Disk(disk) => disk.bounds(),
+            Shape::Quad(quad) => quad.bounds(),
+            Shape::Cylinder(cylinder) => cylinder.bounds(),
+            Shape::Cone(cone) => cone.bounds(),
+            Shape::Capsule(capsule) => capsule.bounds(),
+            Shape::Torus(torus) => torus.bounds(),
+            Shape::Csg(csg) => csg.bounds(),
+            Shape::Sdf(sdf) => sdf.bounds(),
+            Shape::Heightfield(heightfield) => heightfield.bounds(),
+        }
+    }
+
+    fn get_material(&self) -> &Material {
+        match self {
+            Shape::Sphere(sphere) => sphere.get_material(),
+            Shape::Cube(cube) => cube.get_material(),
+            Shape::Instance(instance) => instance.get_material(),
+            Shape::Plane(plane) => plane.get_material(),
+            Shape::Disk(disk) => disk.get_material(),
+            Shape::Quad(quad) => quad.get_material(),
+            Shape::Cylinder(cylinder) => cylinder.get_material(),
+            Shape::Cone(cone) => cone.get_material(),
+            Shape::Capsule(capsule) => capsule.get_material(),
+            Shape::Torus(torus) => torus.get_material(),
+            Shape::Csg(csg) => csg.get_material(),
+            Shape::Sdf(sdf) => sdf.get_material(),
+            Shape::Heightfield(heightfield) => heightfield.get_material(),
+        }
+    }
+
+    fn intersection(&self, ray: &Ray, t: f64) -> Intersection<'_> {
+        match self {
+            Shape::Sphere(sphere) => sphere.intersection(ray, t),
+            Shape::Cube(cube) => cube.intersection(ray, t),
+            Shape::Instance(instance) => instance.intersection(ray, t),
+            Shape::Plane(plane) => plane.intersection(ray, t),
+            Shape::Disk(disk) => disk.intersection(ray, t),
+            Shape::Quad(quad) => quad.intersection(ray, t),
+            Shape::Cylinder(cylinder) => cylinder.intersection(ray, t),
+            Shape::Cone(cone) => cone.intersection(ray, t),
+            Shape::Capsule(capsule) => capsule.intersection(ray, t),
+            Shape::Torus(torus) => torus.intersection(ray, t),
+            Shape::Csg(csg) => csg.intersection(ray, t),
+            Shape::Sdf(sdf) => sdf.intersection(ray, t),
+            Shape::Heightfield(heightfield) => heightfield.intersection(ray, t),
+        }
+    }
+}
+
+// Rust SceneObject struct
+#[derive(Debug, Clone)]
+pub(crate) struct SceneObject {
+    pub id: ObjectId,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub shape: Shape,
+}
+
+impl SceneObject {
+    pub fn new(id: ObjectId, shape: Shape) -> SceneObject {
+        SceneObject {
+            id,
+            name: String::new(),
+            tags: Vec::new(),
+            shape,
+        }
+    }
+}
+
+// Rust ObjectInfo struct, a description of an object in the scene for JavaScript
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    id: ObjectId,
+    kind: String,
+    name: String,
+    tags: Vec<String>,
+}
+
+#[wasm_bindgen]
+impl ObjectInfo {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    // Kind of the object, for example "sphere" or "cube"
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.kind.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+}
+
+impl From<&SceneObject> for ObjectInfo {
+    fn from(object: &SceneObject) -> ObjectInfo {
+        ObjectInfo {
+            id: object.id,
+            kind: object.shape.kind().to_string(),
+            name: object.name.clone(),
+            tags: object.tags.clone(),
+        }
+    }
+}
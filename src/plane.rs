@@ -0,0 +1,73 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb, init_panic_hook, intersection::Intersectable, material::Material, ray::Ray,
+    vector::Vector,
+};
+
+// Rust Plane struct, an infinite plane through a point
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Plane {
+    pub point: Vector,
+    pub normal: Vector,
+    pub material: Material,
+}
+
+impl Intersectable for Plane {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let denominator = self.normal.dot(&ray.direction);
+
+        // Ray is parallel to the plane
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = self.normal.dot(&(self.point - ray.origin)) / denominator;
+
+        if ray.contains(t) {
+            return Some(t);
+        }
+
+        // Ray does not intersect the plane
+        None
+    }
+
+    fn calculate_normal(&self, _point: &Vector) -> Vector {
+        self.normal.normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Coordinates in the plane in world units, measured from the point of the plane
+        let (tangent, bitangent) = self.normal.normalize().orthonormal_basis();
+        let offset = *point - self.point;
+
+        (offset.dot(&tangent), offset.dot(&bitangent))
+    }
+
+    fn calculate_tangents(&self, _point: &Vector) -> (Vector, Vector) {
+        self.normal.normalize().orthonormal_basis()
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::infinite()
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+#[wasm_bindgen]
+impl Plane {
+    #[wasm_bindgen(constructor)]
+    pub fn new(point: Vector, normal: Vector, material: Material) -> Plane {
+        init_panic_hook();
+
+        Plane {
+            point,
+            normal,
+            material,
+        }
+    }
+}
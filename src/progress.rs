@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+// Rust Progress struct
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Progress {
+    pub fraction: f64, // Fraction of the whole render that is complete, between 0 and 1
+    pub samples_per_pixel: u32, // Number of samples accumulated in every pixel so far
+    pub estimated_time_remaining: f64, // Estimated time left in seconds
+}
+
+impl Progress {
+    pub fn new(fraction: f64, samples_per_pixel: u32, elapsed_time: f64) -> Progress {
+        // Extrapolate the time left from the time spent on the part that is done
+        let estimated_time_remaining = if fraction > 0.0 {
+            elapsed_time * (1.0 - fraction) / fraction
+        } else {
+            f64::INFINITY
+        };
+
+        Progress {
+            fraction,
+            samples_per_pixel,
+            estimated_time_remaining,
+        }
+    }
+}
+
+// Trait for native callers that want to follow the progress of a render
+pub trait ProgressReporter: Debug {
+    fn on_progress(&mut self, progress: &Progress);
+    fn on_complete(&mut self, progress: &Progress);
+}
+
+// Rust JsProgressReporter struct, forwards the progress to JavaScript callbacks
+#[derive(Debug, Clone, Default)]
+pub struct JsProgressReporter {
+    on_progress: Option<Function>,
+    on_complete: Option<Function>,
+}
+
+impl JsProgressReporter {
+    pub fn new(on_progress: Option<Function>, on_complete: Option<Function>) -> JsProgressReporter {
+        JsProgressReporter {
+            on_progress,
+            on_complete,
+        }
+    }
+}
+
+impl ProgressReporter for JsProgressReporter {
+    fn on_progress(&mut self, progress: &Progress) {
+        if let Some(callback) = &self.on_progress {
+            // Errors thrown by the callback should not stop the render
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(*progress));
+        }
+    }
+
+    fn on_complete(&mut self, progress: &Progress) {
+        if let Some(callback) = &self.on_complete {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(*progress));
+        }
+    }
+}
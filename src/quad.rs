@@ -0,0 +1,91 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb, init_panic_hook, intersection::Intersectable, material::Material, ray::Ray,
+    vector::Vector,
+};
+
+// Rust Quad struct, a parallelogram spanned by two edges from a corner
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Quad {
+    pub corner: Vector,
+    pub edge_u: Vector,
+    pub edge_v: Vector,
+    pub material: Material,
+}
+
+impl Intersectable for Quad {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let normal = self.edge_u.cross(&self.edge_v);
+        let denominator = normal.dot(&ray.direction);
+
+        // Ray is parallel to the quad
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = normal.dot(&(self.corner - ray.origin)) / denominator;
+
+        if ray.contains(t) {
+            // Check if the point on the plane of the quad is within both edges
+            let intersection_point = ray.point_at_parameter(t);
+            let (u, v) = self.calculate_uv(&intersection_point);
+
+            if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+                return Some(t);
+            }
+        }
+
+        // Ray does not intersect the quad
+        None
+    }
+
+    fn calculate_normal(&self, _point: &Vector) -> Vector {
+        self.edge_u.cross(&self.edge_v).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Solve point = corner + edge_u * u + edge_v * v in the plane of the quad
+        let normal = self.edge_u.cross(&self.edge_v);
+        let w = normal / normal.dot(&normal);
+        let offset = *point - self.corner;
+
+        let u = w.dot(&offset.cross(&self.edge_v));
+        let v = w.dot(&self.edge_u.cross(&offset));
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, _point: &Vector) -> (Vector, Vector) {
+        (self.edge_u, self.edge_v)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let opposite = self.corner + self.edge_u + self.edge_v;
+
+        Aabb::new(self.corner, self.corner)
+            .include_point(&(self.corner + self.edge_u))
+            .include_point(&(self.corner + self.edge_v))
+            .include_point(&opposite)
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+#[wasm_bindgen]
+impl Quad {
+    #[wasm_bindgen(constructor)]
+    pub fn new(corner: Vector, edge_u: Vector, edge_v: Vector, material: Material) -> Quad {
+        init_panic_hook();
+
+        Quad {
+            corner,
+            edge_u,
+            edge_v,
+            material,
+        }
+    }
+}
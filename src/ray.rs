@@ -2,12 +2,43 @@ use wasm_bindgen::prelude::*;
 
 use crate::{init_panic_hook, vector::Vector};
 
+// Number of rounding errors allowed for a point found on a surface, generous enough for the
+// quartic of the torus and the iterations of the other solvers
+const POINT_ERROR_ULPS: f64 = 1024.0;
+
+// Bound on the relative error after n floating point operations, gamma(n) in PBRT
+fn gamma(n: f64) -> f64 {
+    let machine_epsilon = f64::EPSILON * 0.5;
+
+    (n * machine_epsilon) / (1.0 - n * machine_epsilon)
+}
+
+// Next representable float towards positive infinity, f64::next_up needs a newer compiler
+fn next_float_up(value: f64) -> f64 {
+    if value.is_nan() || value == f64::INFINITY {
+        return value;
+    }
+
+    // Negative zero becomes positive zero so that it steps to the smallest positive float
+    let value = if value == 0.0 { 0.0 } else { value };
+    let bits = value.to_bits();
+    let bits = if value >= 0.0 { bits + 1 } else { bits - 1 };
+
+    f64::from_bits(bits)
+}
+
+fn next_float_down(value: f64) -> f64 {
+    -next_float_up(-value)
+}
+
 // Rust Ray struct
 #[wasm_bindgen]
 #[derive(Debug, Copy, Clone)]
 pub struct Ray {
     pub origin: Vector,
     pub direction: Vector,
+    pub t_min: f64, // Only hits with t between t_min and t_max count
+    pub t_max: f64,
 }
 
 #[wasm_bindgen]
@@ -16,7 +47,17 @@ impl Ray {
     pub fn new(origin: Vector, direction: Vector) -> Ray {
         init_panic_hook();
 
-        Ray { origin, direction }
+        Ray {
+            origin,
+            direction,
+            t_min: 0.0,
+            t_max: f64::INFINITY,
+        }
+    }
+
+    // Method to check if a hit at t is within the interval of the ray
+    pub fn contains(&self, t: f64) -> bool {
+        t > self.t_min && t < self.t_max
     }
 
     pub fn point_at_parameter(&self, t: f64) -> Vector {
@@ -40,3 +81,57 @@ impl Ray {
         white * (1.0 - t) + (blue * t)
     }
 }
+
+impl Ray {
+    // Method to get a bound on the floating point error of the point at t along the ray
+    pub fn point_error(&self, t: f64) -> Vector {
+        (self.origin.abs() + (self.direction * t).abs()) * gamma(POINT_ERROR_ULPS)
+    }
+
+    // Method to start a ray at a point on a surface without hitting the same surface again.
+    // The origin is pushed along the normal just past the error bound of the point, to the side the
+    // direction goes to, and then rounded away from the surface.
+    pub fn spawn(point: &Vector, error: &Vector, normal: &Vector, direction: Vector) -> Ray {
+        let distance = normal.abs().dot(error);
+        let mut offset = *normal * distance;
+        if direction.dot(normal) < 0.0 {
+            offset = offset * -1.0;
+        }
+
+        let round_away = |value: f64, offset: f64| {
+            if offset > 0.0 {
+                next_float_up(value)
+            } else if offset < 0.0 {
+                next_float_down(value)
+            } else {
+                value
+            }
+        };
+
+        let origin = *point + offset;
+        let origin = Vector {
+            x: round_away(origin.x, offset.x),
+            y: round_away(origin.y, offset.y),
+            z: round_away(origin.z, offset.z),
+        };
+
+        Ray::new(origin, direction)
+    }
+}
+
+// Rust RayCone struct, how wide a ray is, used to pick the level of detail of textures
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct RayCone {
+    pub width: f64,
+    pub spread: f64, // Growth of the width per unit of distance
+}
+
+impl RayCone {
+    // Method to get the cone after it has travelled some distance
+    pub fn at(&self, distance: f64) -> RayCone {
+        RayCone {
+            width: self.width + self.spread * distance,
+            spread: self.spread,
+        }
+    }
+}
@@ -0,0 +1,625 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    camera::Camera,
+    capsule::Capsule,
+    cone::Cone,
+    csg::Csg,
+    cube::Cube,
+    cylinder::Cylinder,
+    disk::Disk,
+    heightfield::Heightfield,
+    init_panic_hook,
+    instance::Instance,
+    intersection::{project_on_surface, Intersectable, Intersection},
+    material::Material,
+    medium::Medium,
+    object::{ObjectId, ObjectInfo, SceneObject, Shape},
+    plane::Plane,
+    quad::Quad,
+    ray::Ray,
+    sdf::Sdf,
+    shader::{NodeId, Shader, ShaderContext, ShaderId},
+    sphere::Sphere,
+    texture::{Texture, TextureId, TextureQuery},
+    torus::Torus,
+    vector::Vector,
+    volume::{Volume, VolumeId},
+};
+
+// Rust RaycastHit struct
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct RaycastHit {
+    pub object_id: ObjectId,
+    pub distance: f64, // Distance from the origin of the ray to the hit
+    pub position: Vector,
+    pub normal: Vector,   // Faces against the ray
+    pub front_face: bool, // Whether the outside of the surface was hit
+    pub u: f64,           // Texture coordinates of the hit
+    pub v: f64,
+    pub material: Material,
+}
+
+// Rust Scene struct
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    pub(crate) objects: Vec<SceneObject>,
+    next_id: ObjectId,
+    pub(crate) camera: Camera,
+    pub(crate) textures: Vec<Texture>,
+    pub(crate) shaders: Vec<Shader>,
+    pub(crate) volumes: Vec<Volume>,
+    pub(crate) fog: Option<Medium>, // Medium everywhere outside the objects that bound their own
+}
+
+#[wasm_bindgen]
+impl Scene {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Scene {
+        init_panic_hook();
+
+        Scene {
+            objects: Vec::new(),
+            next_id: 0,
+            camera: Camera::default(),
+            textures: Vec::new(),
+            shaders: Vec::new(),
+            volumes: Vec::new(),
+            fog: None,
+        }
+    }
+
+    pub fn add_sphere(&mut self, sphere: Sphere) -> ObjectId {
+        self.add_shape(Shape::Sphere(sphere))
+    }
+
+    pub fn add_cube(&mut self, cube: Cube) -> ObjectId {
+        self.add_shape(Shape::Cube(cube))
+    }
+
+    pub fn add_instance(&mut self, instance: &Instance) -> ObjectId {
+        self.add_shape(Shape::Instance(instance.clone()))
+    }
+
+    pub fn add_plane(&mut self, plane: Plane) -> ObjectId {
+        self.add_shape(Shape::Plane(plane))
+    }
+
+    pub fn add_disk(&mut self, disk: Disk) -> ObjectId {
+        self.add_shape(Shape::Disk(disk))
+    }
+
+    pub fn add_quad(&mut self, quad: Quad) -> ObjectId {
+        self.add_shape(Shape::Quad(quad))
+    }
+
+    pub fn add_cylinder(&mut self, cylinder: Cylinder) -> ObjectId {
+        self.add_shape(Shape::Cylinder(cylinder))
+    }
+
+    pub fn add_cone(&mut self, cone: Cone) -> ObjectId {
+        self.add_shape(Shape::Cone(cone))
+    }
+
+    pub fn add_capsule(&mut self, capsule: Capsule) -> ObjectId {
+        self.add_shape(Shape::Capsule(capsule))
+    }
+
+    pub fn add_torus(&mut self, torus: Torus) -> ObjectId {
+        self.add_shape(Shape::Torus(torus))
+    }
+
+    pub fn add_csg(&mut self, csg: &Csg) -> ObjectId {
+        self.add_shape(csg.shape().clone())
+    }
+
+    pub fn add_sdf(&mut self, sdf: &Sdf, material: Material) -> ObjectId {
+        self.add_shape(Shape::Sdf(sdf.object(material)))
+    }
+
+    pub fn add_heightfield(&mut self, heightfield: &Heightfield) -> ObjectId {
+        self.add_shape(Shape::Heightfield(heightfield.clone()))
+    }
+
+    // Textures are used by materials through the returned id
+    pub fn add_texture(&mut self, texture: &Texture) -> TextureId {
+        self.textures.push(texture.clone());
+
+        (self.textures.len() - 1) as TextureId
+    }
+
+    pub fn get_texture(&self, id: TextureId) -> Option<Texture> {
+        self.textures.get(id as usize).cloned()
+    }
+
+    pub fn update_texture(&mut self, id: TextureId, texture: &Texture) -> Result<(), JsValue> {
+        let slot = self
+            .textures
+            .get_mut(id as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("No texture with id {id}")))?;
+        *slot = texture.clone();
+
+        Ok(())
+    }
+
+    // Shaders are used by materials through the returned id
+    pub fn add_shader(&mut self, shader: &Shader) -> ShaderId {
+        self.shaders.push(shader.clone());
+
+        (self.shaders.len() - 1) as ShaderId
+    }
+
+    pub fn get_shader(&self, id: ShaderId) -> Option<Shader> {
+        self.shaders.get(id as usize).cloned()
+    }
+
+    pub fn update_shader(&mut self, id: ShaderId, shader: &Shader) -> Result<(), JsValue> {
+        let slot = self
+            .shaders
+            .get_mut(id as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("No shader with id {id}")))?;
+        *slot = shader.clone();
+
+        Ok(())
+    }
+
+    // Volumes are not objects, they fill their box without a surface
+    pub fn add_volume(&mut self, volume: &Volume) -> VolumeId {
+        self.volumes.push(volume.clone());
+
+        (self.volumes.len() - 1) as VolumeId
+    }
+
+    pub fn get_volume(&self, id: VolumeId) -> Option<Volume> {
+        self.volumes.get(id as usize).cloned()
+    }
+
+    pub fn update_volume(&mut self, id: VolumeId, volume: &Volume) -> Result<(), JsValue> {
+        let slot = self
+            .volumes
+            .get_mut(id as usize)
+            .ok_or_else(|| JsValue::from_str(&format!("No volume with id {id}")))?;
+        *slot = volume.clone();
+
+        Ok(())
+    }
+
+    pub fn set_fog(&mut self, fog: &Medium) {
+        self.fog = Some(*fog);
+    }
+
+    pub fn clear_fog(&mut self) {
+        self.fog = None;
+    }
+
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.camera = camera;
+    }
+
+    pub fn remove(&mut self, id: ObjectId) -> Result<(), JsValue> {
+        let index = self
+            .objects
+            .iter()
+            .position(|object| object.id == id)
+            .ok_or_else(|| object_not_found(id))?;
+
+        self.objects.remove(index);
+
+        Ok(())
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<ObjectInfo> {
+        self.objects
+            .iter()
+            .find(|object| object.id == id)
+            .map(ObjectInfo::from)
+    }
+
+    pub fn get_sphere(&self, id: ObjectId) -> Option<Sphere> {
+        match self.get_object(id)?.shape {
+            Shape::Sphere(sphere) => Some(sphere),
+            _ => None,
+        }
+    }
+
+    pub fn get_cube(&self, id: ObjectId) -> Option<Cube> {
+        match self.get_object(id)?.shape {
+            Shape::Cube(cube) => Some(cube),
+            _ => None,
+        }
+    }
+
+    pub fn get_instance(&self, id: ObjectId) -> Option<Instance> {
+        match &self.get_object(id)?.shape {
+            Shape::Instance(instance) => Some(instance.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn get_plane(&self, id: ObjectId) -> Option<Plane> {
+        match self.get_object(id)?.shape {
+            Shape::Plane(plane) => Some(plane),
+            _ => None,
+        }
+    }
+
+    pub fn get_disk(&self, id: ObjectId) -> Option<Disk> {
+        match self.get_object(id)?.shape {
+            Shape::Disk(disk) => Some(disk),
+            _ => None,
+        }
+    }
+
+    pub fn get_quad(&self, id: ObjectId) -> Option<Quad> {
+        match self.get_object(id)?.shape {
+            Shape::Quad(quad) => Some(quad),
+            _ => None,
+        }
+    }
+
+    pub fn get_cylinder(&self, id: ObjectId) -> Option<Cylinder> {
+        match self.get_object(id)?.shape {
+            Shape::Cylinder(cylinder) => Some(cylinder),
+            _ => None,
+        }
+    }
+
+    pub fn get_cone(&self, id: ObjectId) -> Option<Cone> {
+        match self.get_object(id)?.shape {
+            Shape::Cone(cone) => Some(cone),
+            _ => None,
+        }
+    }
+
+    pub fn get_capsule(&self, id: ObjectId) -> Option<Capsule> {
+        match self.get_object(id)?.shape {
+            Shape::Capsule(capsule) => Some(capsule),
+            _ => None,
+        }
+    }
+
+    pub fn get_torus(&self, id: ObjectId) -> Option<Torus> {
+        match self.get_object(id)?.shape {
+            Shape::Torus(torus) => Some(torus),
+            _ => None,
+        }
+    }
+
+    pub fn get_heightfield(&self, id: ObjectId) -> Option<Heightfield> {
+        match &self.get_object(id)?.shape {
+            Shape::Heightfield(heightfield) => Some(heightfield.clone()),
+            _ => None,
+        }
+    }
+
+    // Axis-aligned box around the object
+    pub fn get_bounds(&self, id: ObjectId) -> Option<Aabb> {
+        Some(self.get_object(id)?.shape.bounds())
+    }
+
+    pub fn update_sphere(&mut self, id: ObjectId, sphere: Sphere) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Sphere(sphere);
+
+        Ok(())
+    }
+
+    pub fn update_cube(&mut self, id: ObjectId, cube: Cube) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Cube(cube);
+
+        Ok(())
+    }
+
+    pub fn update_instance(&mut self, id: ObjectId, instance: &Instance) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Instance(instance.clone());
+
+        Ok(())
+    }
+
+    pub fn update_plane(&mut self, id: ObjectId, plane: Plane) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Plane(plane);
+
+        Ok(())
+    }
+
+    pub fn update_disk(&mut self, id: ObjectId, disk: Disk) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Disk(disk);
+
+        Ok(())
+    }
+
+    pub fn update_quad(&mut self, id: ObjectId, quad: Quad) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Quad(quad);
+
+        Ok(())
+    }
+
+    pub fn update_cylinder(&mut self, id: ObjectId, cylinder: Cylinder) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Cylinder(cylinder);
+
+        Ok(())
+    }
+
+    pub fn update_cone(&mut self, id: ObjectId, cone: Cone) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Cone(cone);
+
+        Ok(())
+    }
+
+    pub fn update_capsule(&mut self, id: ObjectId, capsule: Capsule) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Capsule(capsule);
+
+        Ok(())
+    }
+
+    pub fn update_torus(&mut self, id: ObjectId, torus: Torus) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Torus(torus);
+
+        Ok(())
+    }
+
+    pub fn update_csg(&mut self, id: ObjectId, csg: &Csg) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = csg.shape().clone();
+
+        Ok(())
+    }
+
+    pub fn update_sdf(
+        &mut self,
+        id: ObjectId,
+        sdf: &Sdf,
+        material: Material,
+    ) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Sdf(sdf.object(material));
+
+        Ok(())
+    }
+
+    pub fn update_heightfield(
+        &mut self,
+        id: ObjectId,
+        heightfield: &Heightfield,
+    ) -> Result<(), JsValue> {
+        self.object_mut(id)?.shape = Shape::Heightfield(heightfield.clone());
+
+        Ok(())
+    }
+
+    // List all the objects in the order they were added
+    pub fn list(&self) -> Vec<ObjectInfo> {
+        self.objects.iter().map(ObjectInfo::from).collect()
+    }
+
+    pub fn set_name(&mut self, id: ObjectId, name: String) -> Result<(), JsValue> {
+        self.object_mut(id)?.name = name;
+
+        Ok(())
+    }
+
+    pub fn add_tag(&mut self, id: ObjectId, tag: String) -> Result<(), JsValue> {
+        let object = self.object_mut(id)?;
+        if !object.tags.contains(&tag) {
+            object.tags.push(tag);
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_tag(&mut self, id: ObjectId, tag: &str) -> Result<(), JsValue> {
+        self.object_mut(id)?.tags.retain(|x| x != tag);
+
+        Ok(())
+    }
+
+    // Find the closest object along the ray, the direction does not have to be normalized
+    pub fn raycast(&self, origin: Vector, direction: Vector) -> Option<RaycastHit> {
+        let ray = Ray::new(origin, direction);
+        let intersection = self.closest_intersection(&ray)?;
+
+        Some(RaycastHit {
+            object_id: intersection.object_id,
+            distance: intersection.t * direction.magnitude(),
+            position: intersection.intersection_point,
+            normal: intersection.shading_normal,
+            front_face: intersection.front_face,
+            u: intersection.uv.0,
+            v: intersection.uv.1,
+            material: *intersection.material,
+        })
+    }
+
+    // Find the first object with the given name
+    pub fn find_by_name(&self, name: &str) -> Option<ObjectId> {
+        self.objects
+            .iter()
+            .find(|object| object.name == name)
+            .map(|object| object.id)
+    }
+
+    pub fn find_by_tag(&self, tag: &str) -> Vec<ObjectId> {
+        self.objects
+            .iter()
+            .filter(|object| object.tags.iter().any(|x| x == tag))
+            .map(|object| object.id)
+            .collect()
+    }
+}
+
+impl Scene {
+    fn add_shape(&mut self, shape: Shape) -> ObjectId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.objects.push(SceneObject::new(id, shape));
+
+        id
+    }
+
+    pub(crate) fn closest_intersection(&self, ray: &Ray) -> Option<Intersection<'_>> {
+        let mut closest: Option<(&SceneObject, f64)> = None;
+
+        // Objects only have to be hit closer than the closest hit so far
+        let mut ray = *ray;
+        for object in &self.objects {
+            if let Some(t) = object.shape.intersect(&ray) {
+                if t < ray.t_max {
+                    ray.t_max = t;
+                    closest = Some((object, t));
+                }
+            }
+        }
+
+        // Only the closest hit is worked out in full
+        let (object, t) = closest?;
+        Some(Intersection {
+            object_id: object.id,
+            ..object.shape.intersection(&ray, t)
+        })
+    }
+
+    // Method to get the material at a hit with its textures and shader applied, the cone width is how wide the ray is there
+    pub(crate) fn surface_material(
+        &self,
+        intersection: &Intersection,
+        direction: &Vector,
+        cone_width: f64,
+    ) -> Material {
+        let material = *intersection.material;
+        let query = TextureQuery::new(intersection, cone_width);
+        let sample =
+            |texture: Option<TextureId>| Some(self.textures.get(texture? as usize)?.sample(&query));
+
+        let textured = Material {
+            color: sample(material.color_texture)
+                .map_or(material.color, |texel| material.color * texel),
+            roughness: sample(material.roughness_texture)
+                .map_or(material.roughness, |texel| material.roughness * texel.x),
+            emission_color: sample(material.emission_texture)
+                .map_or(material.emission_color, |texel| {
+                    material.emission_color * texel
+                }),
+            ..material
+        };
+
+        let Some(shader) = material.shader.and_then(|id| self.shaders.get(id as usize)) else {
+            return textured;
+        };
+
+        // Textures the shader uses that are not in the scene are black
+        let context = ShaderContext {
+            query,
+            normal: intersection.shading_normal,
+            direction: *direction,
+        };
+        let texture = |id: TextureId, query: &TextureQuery| {
+            self.textures
+                .get(id as usize)
+                .map_or(Vector::default(), |texture| texture.sample(query))
+        };
+        let values = shader.evaluate(&context, &texture);
+        let evaluate = |node: NodeId| values.get(node);
+        let outputs = shader.outputs();
+        let fraction = |output: Option<NodeId>, value: f64| {
+            output.map_or(value, |node| evaluate(node).x.clamp(0.0, 1.0))
+        };
+
+        Material {
+            color: outputs.color.map_or(textured.color, evaluate),
+            roughness: fraction(outputs.roughness, textured.roughness),
+            emission_color: outputs.emission.map_or(textured.emission_color, evaluate),
+            metallic: fraction(outputs.metallic, textured.metallic),
+            specular: fraction(outputs.specular, textured.specular),
+            anisotropy: fraction(outputs.anisotropy, textured.anisotropy),
+            clearcoat: fraction(outputs.clearcoat, textured.clearcoat),
+            transmission: fraction(outputs.transmission, textured.transmission),
+            sheen: outputs
+                .sheen
+                .map_or(textured.sheen, |node| evaluate(node).x.max(0.0)),
+            subsurface: fraction(outputs.subsurface, textured.subsurface),
+            shader_normal: outputs.normal.map(evaluate),
+            ..textured
+        }
+    }
+
+    // Method to get the normal used for shading at a hit with the normal or bump map of the surface material applied,
+    // a normal from its shader takes the place of the normal map
+    pub(crate) fn shading_normal(
+        &self,
+        intersection: &Intersection,
+        material: &Material,
+        cone_width: f64,
+    ) -> Vector {
+        let normal = intersection.shading_normal;
+        let query = TextureQuery::new(intersection, cone_width);
+
+        // Tangent frame that follows the texture coordinates
+        let tangent = project_on_surface(&intersection.dpdu, &normal).normalize();
+        let bitangent = normal.cross(&tangent);
+        let bitangent = if bitangent.dot(&intersection.dpdv) < 0.0 {
+            bitangent * -1.0
+        } else {
+            bitangent
+        };
+
+        let mut shading_normal = normal;
+        let texel = material.shader_normal.or_else(|| {
+            let texture = self.textures.get(material.normal_map? as usize)?;
+            Some(texture.sample(&query))
+        });
+        if let Some(texel) = texel {
+            let texel = texel * 2.0 - Vector::new(1.0, 1.0, 1.0);
+            shading_normal =
+                (tangent * texel.x + bitangent * texel.y + normal * texel.z).normalize();
+        }
+
+        if let Some(texture) = material
+            .bump_map
+            .and_then(|id| self.textures.get(id as usize))
+        {
+            // Slopes of the height along u and v by finite differences
+            let delta = query.footprint.0.max(query.footprint.1).clamp(1e-4, 1e-2);
+            let (dpdu, dpdv) = (&intersection.dpdu, &intersection.dpdv);
+            let height = texture.sample(&query).x;
+            let height_u = texture.sample(&query.offset(delta, 0.0, dpdu, dpdv)).x;
+            let height_v = texture.sample(&query.offset(0.0, delta, dpdu, dpdv)).x;
+            let slope_u = (height_u - height) / delta * material.bump_strength;
+            let slope_v = (height_v - height) / delta * material.bump_strength;
+
+            // Normal of the surface moved out along the normal by the height
+            let displaced_u = *dpdu + shading_normal * slope_u;
+            let displaced_v = *dpdv + shading_normal * slope_v;
+            let bumped = displaced_u.cross(&displaced_v).normalize();
+            shading_normal = if bumped.dot(&shading_normal) < 0.0 {
+                bumped * -1.0
+            } else {
+                bumped
+            };
+        }
+
+        // A shading normal that points below the surface would light it from behind
+        let geometric_normal = intersection.geometric_normal;
+        let below = shading_normal.dot(&geometric_normal);
+        if below < 0.01 {
+            shading_normal = (shading_normal + geometric_normal * (0.01 - below)).normalize();
+        }
+
+        shading_normal
+    }
+
+    fn get_object(&self, id: ObjectId) -> Option<&SceneObject> {
+        self.objects.iter().find(|object| object.id == id)
+    }
+
+    pub(crate) fn object_mut(&mut self, id: ObjectId) -> Result<&mut SceneObject, JsValue> {
+        self.objects
+            .iter_mut()
+            .find(|object| object.id == id)
+            .ok_or_else(|| object_not_found(id))
+    }
+}
+
+fn object_not_found(id: ObjectId) -> JsValue {
+    JsValue::from_str(&format!("No object with id {}", id))
+}
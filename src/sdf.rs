@@ -0,0 +1,516 @@
+use std::{f64::consts::PI, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::Aabb,
+    init_panic_hook,
+    intersection::{project_on_surface, Intersectable},
+    material::Material,
+    ray::Ray,
+    sphere::spherical_tangents,
+    vector::Vector,
+};
+
+const MAX_STEPS: u32 = 512;
+const MAX_DISTANCE: f64 = 1e4;
+const HIT_DISTANCE: f64 = 1e-5;
+
+// Rust SdfNode enum, a tree of signed distance functions
+#[derive(Debug, Clone)]
+pub(crate) enum SdfNode {
+    Sphere {
+        center: Vector,
+        radius: f64,
+    },
+    Box {
+        center: Vector,
+        half_size: Vector,
+    },
+    RoundBox {
+        center: Vector,
+        half_size: Vector,
+        radius: f64,
+    },
+    Torus {
+        center: Vector,
+        major_radius: f64,
+        minor_radius: f64,
+    },
+    SmoothUnion {
+        left: Rc<SdfNode>,
+        right: Rc<SdfNode>,
+        smoothness: f64,
+    },
+    SmoothSubtract {
+        left: Rc<SdfNode>,
+        right: Rc<SdfNode>,
+        smoothness: f64,
+    },
+    SmoothIntersect {
+        left: Rc<SdfNode>,
+        right: Rc<SdfNode>,
+        smoothness: f64,
+    },
+    Twist {
+        child: Rc<SdfNode>,
+        amount: f64, // Radians of rotation around the y-axis per unit along it
+    },
+    Repeat {
+        child: Rc<SdfNode>,
+        period: Vector, // Distance between the copies along each axis, zero means no repetition
+    },
+    Translate {
+        child: Rc<SdfNode>,
+        offset: Vector,
+    },
+}
+
+impl SdfNode {
+    // Signed distance from the point to the surface, negative inside
+    pub fn distance(&self, p: &Vector) -> f64 {
+        match self {
+            SdfNode::Sphere { center, radius } => (*p - *center).magnitude() - radius,
+            SdfNode::Box { center, half_size } => box_distance(&(*p - *center), half_size),
+            SdfNode::RoundBox {
+                center,
+                half_size,
+                radius,
+            } => {
+                let inner = *half_size - Vector::new(*radius, *radius, *radius);
+                box_distance(&(*p - *center), &inner) - radius
+            }
+            SdfNode::Torus {
+                center,
+                major_radius,
+                minor_radius,
+            } => {
+                let q = *p - *center;
+                let ring = (q.x * q.x + q.z * q.z).sqrt() - major_radius;
+                (ring * ring + q.y * q.y).sqrt() - minor_radius
+            }
+            SdfNode::SmoothUnion {
+                left,
+                right,
+                smoothness,
+            } => {
+                let (a, b) = (left.distance(p), right.distance(p));
+                if *smoothness <= 0.0 {
+                    return a.min(b);
+                }
+                let h = (0.5 + 0.5 * (b - a) / smoothness).clamp(0.0, 1.0);
+                mix(b, a, h) - smoothness * h * (1.0 - h)
+            }
+            SdfNode::SmoothSubtract {
+                left,
+                right,
+                smoothness,
+            } => {
+                let (a, b) = (left.distance(p), right.distance(p));
+                if *smoothness <= 0.0 {
+                    return a.max(-b);
+                }
+                let h = (0.5 - 0.5 * (a + b) / smoothness).clamp(0.0, 1.0);
+                mix(a, -b, h) + smoothness * h * (1.0 - h)
+            }
+            SdfNode::SmoothIntersect {
+                left,
+                right,
+                smoothness,
+            } => {
+                let (a, b) = (left.distance(p), right.distance(p));
+                if *smoothness <= 0.0 {
+                    return a.max(b);
+                }
+                let h = (0.5 - 0.5 * (b - a) / smoothness).clamp(0.0, 1.0);
+                mix(b, a, h) + smoothness * h * (1.0 - h)
+            }
+            SdfNode::Twist { child, amount } => {
+                // Rotate the point back by the angle at its height
+                let (sin, cos) = (-amount * p.y).sin_cos();
+                let q = Vector::new(cos * p.x - sin * p.z, p.y, sin * p.x + cos * p.z);
+                child.distance(&q)
+            }
+            SdfNode::Repeat { child, period } => {
+                let repeat = |x: f64, period: f64| {
+                    if period > 0.0 {
+                        x - period * (x / period).round()
+                    } else {
+                        x
+                    }
+                };
+                let q = Vector::new(
+                    repeat(p.x, period.x),
+                    repeat(p.y, period.y),
+                    repeat(p.z, period.z),
+                );
+                child.distance(&q)
+            }
+            SdfNode::Translate { child, offset } => child.distance(&(*p - *offset)),
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        match self {
+            SdfNode::Sphere { center, radius } => {
+                Aabb::from_center(*center, Vector::new(*radius, *radius, *radius))
+            }
+            SdfNode::Box { center, half_size }
+            | SdfNode::RoundBox {
+                center, half_size, ..
+            } => Aabb::from_center(*center, *half_size),
+            SdfNode::Torus {
+                center,
+                major_radius,
+                minor_radius,
+            } => {
+                let outer = major_radius + minor_radius;
+                Aabb::from_center(*center, Vector::new(outer, *minor_radius, outer))
+            }
+            SdfNode::SmoothUnion {
+                left,
+                right,
+                smoothness,
+            } => grow(&left.bounds().union(&right.bounds()), *smoothness),
+            SdfNode::SmoothSubtract {
+                left, smoothness, ..
+            }
+            | SdfNode::SmoothIntersect {
+                left, smoothness, ..
+            } => grow(&left.bounds(), *smoothness),
+            SdfNode::Twist { child, .. } => {
+                // Twisting keeps the height and the distance from the y-axis
+                let bounds = child.bounds();
+                let radius = axis_radius(&bounds);
+                Aabb::new(
+                    Vector::new(-radius, bounds.min.y, -radius),
+                    Vector::new(radius, bounds.max.y, radius),
+                )
+            }
+            SdfNode::Repeat { .. } => Aabb::infinite(),
+            SdfNode::Translate { child, offset } => {
+                let bounds = child.bounds();
+                Aabb::new(bounds.min + *offset, bounds.max + *offset)
+            }
+        }
+    }
+
+    // Upper bound of how fast the distance can change, sphere tracing divides by it to not overstep
+    pub fn lipschitz(&self) -> f64 {
+        match self {
+            SdfNode::SmoothUnion { left, right, .. }
+            | SdfNode::SmoothSubtract { left, right, .. }
+            | SdfNode::SmoothIntersect { left, right, .. } => {
+                left.lipschitz().max(right.lipschitz())
+            }
+            SdfNode::Twist { child, amount } => {
+                let radius = axis_radius(&child.bounds());
+                child.lipschitz() * (1.0 + (amount * radius).powi(2)).sqrt()
+            }
+            SdfNode::Repeat { child, .. } | SdfNode::Translate { child, .. } => child.lipschitz(),
+            _ => 1.0,
+        }
+    }
+}
+
+fn box_distance(p: &Vector, half_size: &Vector) -> f64 {
+    let q = Vector::new(
+        p.x.abs() - half_size.x,
+        p.y.abs() - half_size.y,
+        p.z.abs() - half_size.z,
+    );
+    let outside = Vector::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0));
+
+    outside.magnitude() + q.x.max(q.y).max(q.z).min(0.0)
+}
+
+// Farthest distance of the box from the y-axis
+fn axis_radius(bounds: &Aabb) -> f64 {
+    bounds
+        .corners()
+        .iter()
+        .map(|corner| (corner.x * corner.x + corner.z * corner.z).sqrt())
+        .fold(0.0, f64::max)
+}
+
+fn mix(a: f64, b: f64, h: f64) -> f64 {
+    a * (1.0 - h) + b * h
+}
+
+fn grow(bounds: &Aabb, amount: f64) -> Aabb {
+    let amount = Vector::new(amount, amount, amount);
+    Aabb::new(bounds.min - amount, bounds.max + amount)
+}
+
+// Method to get where the line of the ray enters and leaves a box
+fn clip_to_bounds(ray: &Ray, bounds: &Aabb) -> (f64, f64) {
+    if !bounds.is_finite() {
+        return (ray.t_min, ray.t_max.min(MAX_DISTANCE));
+    }
+
+    let mut t_min = f64::NEG_INFINITY;
+    let mut t_max = f64::INFINITY;
+    for (origin, direction, min, max) in [
+        (ray.origin.x, ray.direction.x, bounds.min.x, bounds.max.x),
+        (ray.origin.y, ray.direction.y, bounds.min.y, bounds.max.y),
+        (ray.origin.z, ray.direction.z, bounds.min.z, bounds.max.z),
+    ] {
+        if direction == 0.0 {
+            if origin < min || origin > max {
+                return (1.0, 0.0);
+            }
+            continue;
+        }
+        let t_1 = (min - origin) / direction;
+        let t_2 = (max - origin) / direction;
+        t_min = t_min.max(t_1.min(t_2));
+        t_max = t_max.min(t_1.max(t_2));
+    }
+
+    (t_min.max(ray.t_min), t_max.min(ray.t_max))
+}
+
+// Rust SdfObject struct, a distance field in the scene rendered by sphere tracing
+#[derive(Debug, Clone)]
+pub(crate) struct SdfObject {
+    node: Rc<SdfNode>,
+    pub material: Material,
+    bounds: Aabb,   // Of the whole tree, which is too slow to walk for every ray
+    lipschitz: f64, // Of the whole tree
+}
+
+impl SdfObject {
+    pub fn new(node: Rc<SdfNode>, material: Material) -> SdfObject {
+        SdfObject {
+            bounds: node.bounds(),
+            lipschitz: node.lipschitz(),
+            node,
+            material,
+        }
+    }
+
+    // Point that is moved by set_center
+    pub fn center(&self) -> Vector {
+        match self.node.as_ref() {
+            SdfNode::Translate { offset, .. } => *offset,
+            _ => Vector::default(),
+        }
+    }
+
+    pub fn set_center(&mut self, center: Vector) {
+        // Replace the translation at the root instead of stacking another one on top
+        let child = match self.node.as_ref() {
+            SdfNode::Translate { child, .. } => child.clone(),
+            _ => self.node.clone(),
+        };
+
+        *self = SdfObject::new(
+            Rc::new(SdfNode::Translate {
+                child,
+                offset: center,
+            }),
+            self.material,
+        );
+    }
+}
+
+impl Intersectable for SdfObject {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        let bounds = grow(&self.bounds, 2.0 * HIT_DISTANCE);
+        let (t_start, t_end) = clip_to_bounds(ray, &bounds);
+        if t_start > t_end {
+            return None;
+        }
+
+        // March along the ray, steps are in t so divide by the length of the direction
+        let scale = 1.0 / (ray.direction.magnitude() * self.lipschitz);
+        let mut t = t_start;
+
+        // Rays starting inside march on the negated distance to find where they leave. A ray that
+        // starts on the surface is inside if it goes against the gradient, and it can only hit
+        // once it has left the surface
+        let start = ray.point_at_parameter(t);
+        let start_distance = self.node.distance(&start);
+        let sign = if start_distance.abs() < HIT_DISTANCE {
+            self.calculate_normal(&start).dot(&ray.direction).signum()
+        } else {
+            start_distance.signum()
+        };
+        let mut left_surface = start_distance.abs() >= HIT_DISTANCE;
+
+        for _ in 0..MAX_STEPS {
+            let distance = sign * self.node.distance(&ray.point_at_parameter(t));
+            if distance < HIT_DISTANCE && left_surface && ray.contains(t) {
+                return Some(t);
+            }
+            left_surface |= distance >= HIT_DISTANCE;
+
+            t += distance.max(HIT_DISTANCE) * scale;
+            if t > t_end {
+                break;
+            }
+        }
+
+        // Ray does not hit the surface
+        None
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        // Gradient of the distance with the tetrahedron technique, four samples instead of six
+        let h = 1e-5;
+        let offsets = [
+            Vector::new(1.0, -1.0, -1.0),
+            Vector::new(-1.0, -1.0, 1.0),
+            Vector::new(-1.0, 1.0, -1.0),
+            Vector::new(1.0, 1.0, 1.0),
+        ];
+
+        offsets
+            .iter()
+            .fold(Vector::default(), |gradient, offset| {
+                gradient + *offset * self.node.distance(&(*point + *offset * h))
+            })
+            .normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Spherical coordinates around the center of the bounds
+        let center = if self.bounds.is_finite() {
+            self.bounds.center()
+        } else {
+            Vector::default()
+        };
+        let direction = (*point - center).normalize();
+
+        let u = 0.5 + direction.z.atan2(direction.x) / (2.0 * PI);
+        let v = (-direction.y).clamp(-1.0, 1.0).acos() / PI;
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        // The spherical coordinates projected on the surface
+        let center = if self.bounds.is_finite() {
+            self.bounds.center()
+        } else {
+            Vector::default()
+        };
+        let (dpdu, dpdv) = spherical_tangents(&(*point - center));
+        let normal = self.calculate_normal(point);
+
+        (
+            project_on_surface(&dpdu, &normal),
+            project_on_surface(&dpdv, &normal),
+        )
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+// Rust Sdf struct, used from JavaScript to build a tree of distance functions
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Sdf {
+    node: Rc<SdfNode>,
+}
+
+impl Sdf {
+    pub(crate) fn object(&self, material: Material) -> SdfObject {
+        SdfObject::new(self.node.clone(), material)
+    }
+
+    fn from_node(node: SdfNode) -> Sdf {
+        init_panic_hook();
+
+        Sdf {
+            node: Rc::new(node),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Sdf {
+    pub fn sphere(center: Vector, radius: f64) -> Sdf {
+        Sdf::from_node(SdfNode::Sphere { center, radius })
+    }
+
+    pub fn cuboid(center: Vector, size: Vector) -> Sdf {
+        Sdf::from_node(SdfNode::Box {
+            center,
+            half_size: size / 2.0,
+        })
+    }
+
+    // Box with its edges rounded by the radius, the size includes the rounding
+    pub fn round_box(center: Vector, size: Vector, radius: f64) -> Sdf {
+        Sdf::from_node(SdfNode::RoundBox {
+            center,
+            half_size: size / 2.0,
+            radius,
+        })
+    }
+
+    // Torus around the y-axis through the center
+    pub fn torus(center: Vector, major_radius: f64, minor_radius: f64) -> Sdf {
+        Sdf::from_node(SdfNode::Torus {
+            center,
+            major_radius,
+            minor_radius,
+        })
+    }
+
+    // A smoothness of zero gives a sharp union
+    pub fn smooth_union(&self, other: &Sdf, smoothness: f64) -> Sdf {
+        Sdf::from_node(SdfNode::SmoothUnion {
+            left: self.node.clone(),
+            right: other.node.clone(),
+            smoothness,
+        })
+    }
+
+    // Carve the other shape out of this one
+    pub fn smooth_subtract(&self, other: &Sdf, smoothness: f64) -> Sdf {
+        Sdf::from_node(SdfNode::SmoothSubtract {
+            left: self.node.clone(),
+            right: other.node.clone(),
+            smoothness,
+        })
+    }
+
+    pub fn smooth_intersect(&self, other: &Sdf, smoothness: f64) -> Sdf {
+        Sdf::from_node(SdfNode::SmoothIntersect {
+            left: self.node.clone(),
+            right: other.node.clone(),
+            smoothness,
+        })
+    }
+
+    // Twist around the y-axis, the amount is in degrees per unit along the axis
+    pub fn twist(&self, amount: f64) -> Sdf {
+        Sdf::from_node(SdfNode::Twist {
+            child: self.node.clone(),
+            amount: amount.to_radians(),
+        })
+    }
+
+    pub fn translate(&self, offset: Vector) -> Sdf {
+        Sdf::from_node(SdfNode::Translate {
+            child: self.node.clone(),
+            offset,
+        })
+    }
+
+    // Repeat the shape forever with the given period along each axis, zero means no repetition
+    pub fn repeat(&self, period: Vector) -> Sdf {
+        Sdf::from_node(SdfNode::Repeat {
+            child: self.node.clone(),
+            period,
+        })
+    }
+}
@@ -0,0 +1,515 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::{init_panic_hook, texture::TextureId, texture::TextureQuery, vector::Vector};
+
+// Handle of a shader in the scene
+pub type ShaderId = u32;
+
+// Index of a node in its shader, a node can only use the nodes before it
+pub type NodeId = u32;
+
+// Most nodes a shader can have, so that the values of all of them fit on the stack while a hit is shaded
+const MAX_NODES: usize = 128;
+
+// Rust MathOperation enum
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MathOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Minimum,
+    Maximum,
+}
+
+impl MathOperation {
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        match self {
+            MathOperation::Add => a + b,
+            MathOperation::Subtract => a - b,
+            MathOperation::Multiply => a * b,
+            MathOperation::Divide if b == 0.0 => 0.0,
+            MathOperation::Divide => a / b,
+            MathOperation::Power => a.max(0.0).powf(b),
+            MathOperation::Minimum => a.min(b),
+            MathOperation::Maximum => a.max(b),
+        }
+    }
+}
+
+// Rust ColorStop struct, a color at a position of a color ramp
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub(crate) struct ColorStop {
+    pub position: f64,
+    pub color: Vector,
+}
+
+// Rust ShaderNode enum, every node gives three channels, single values are gray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ShaderNode {
+    Constant {
+        value: Vector,
+    },
+    // Texture coordinates as the first two channels
+    Uv,
+    Position,
+    Normal,
+    // 1 where the surface faces the camera and 0 where it is seen edge-on
+    FacingRatio,
+    // Schlick's approximation of the reflectance of a dielectric
+    Fresnel {
+        ior: f64,
+    },
+    Texture {
+        texture: TextureId,
+        uv: Option<NodeId>, // Texture coordinates of the hit if not given
+    },
+    UvTransform {
+        uv: Option<NodeId>,
+        scale: (f64, f64),
+        rotation: f64, // Degrees
+        offset: (f64, f64),
+    },
+    Math {
+        operation: MathOperation,
+        a: NodeId,
+        b: NodeId,
+    },
+    Mix {
+        a: NodeId,
+        b: NodeId,
+        factor: NodeId,
+    },
+    ColorRamp {
+        input: NodeId,
+        stops: Vec<ColorStop>,
+    },
+}
+
+impl ShaderNode {
+    fn inputs(&self) -> Vec<NodeId> {
+        match self {
+            ShaderNode::Texture { uv, .. } | ShaderNode::UvTransform { uv, .. } => {
+                uv.iter().copied().collect()
+            }
+            ShaderNode::Math { a, b, .. } => vec![*a, *b],
+            ShaderNode::Mix { a, b, factor } => vec![*a, *b, *factor],
+            ShaderNode::ColorRamp { input, .. } => vec![*input],
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Rust ShaderContext struct, what a shader knows about the hit
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ShaderContext {
+    pub query: TextureQuery,
+    pub normal: Vector,    // Shading normal facing against the ray
+    pub direction: Vector, // Direction of the ray
+}
+
+// Rust ShaderOutputs struct, the nodes that give the parameters of the material
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct ShaderOutputs {
+    pub color: Option<NodeId>,
+    pub roughness: Option<NodeId>, // First channel, like the other numbers
+    pub emission: Option<NodeId>,
+    pub metallic: Option<NodeId>,
+    pub specular: Option<NodeId>,
+    pub anisotropy: Option<NodeId>,
+    pub clearcoat: Option<NodeId>,
+    pub transmission: Option<NodeId>,
+    pub sheen: Option<NodeId>,
+    pub subsurface: Option<NodeId>,
+    pub normal: Option<NodeId>, // In tangent space from 0 to 1 like a normal map
+}
+
+impl ShaderOutputs {
+    fn nodes(&self) -> [Option<NodeId>; 11] {
+        [
+            self.color,
+            self.roughness,
+            self.emission,
+            self.metallic,
+            self.specular,
+            self.anisotropy,
+            self.clearcoat,
+            self.transmission,
+            self.sheen,
+            self.subsurface,
+            self.normal,
+        ]
+    }
+}
+
+// Rust ShaderValues struct, the values of the nodes of a shader at a hit, kept in place so that nothing is allocated
+pub(crate) struct ShaderValues {
+    values: [Vector; MAX_NODES],
+    count: usize,
+}
+
+impl ShaderValues {
+    pub fn get(&self, node: NodeId) -> Vector {
+        self.values[..self.count][node as usize]
+    }
+}
+
+// Rust Shader struct, a graph of nodes that is evaluated at every hit to give material parameters
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Shader {
+    nodes: Vec<ShaderNode>,
+    #[serde(default)]
+    outputs: ShaderOutputs,
+}
+
+impl Shader {
+    // Every node goes through here, from the builder methods and from JSON
+    fn add_node(&mut self, mut node: ShaderNode) -> Result<NodeId, JsValue> {
+        if self.nodes.len() >= MAX_NODES {
+            return Err(JsValue::from_str(&format!(
+                "A shader can have at most {MAX_NODES} nodes"
+            )));
+        }
+
+        let id = self.nodes.len() as NodeId;
+        if node.inputs().iter().any(|input| *input >= id) {
+            return Err(JsValue::from_str(
+                "A shader node can only use nodes that were added before it",
+            ));
+        }
+
+        // The stops of a color ramp are looked up in order of their positions
+        if let ShaderNode::ColorRamp { stops, .. } = &mut node {
+            if stops.iter().any(|stop| stop.position.is_nan()) {
+                return Err(JsValue::from_str(
+                    "The positions of a color ramp have to be numbers",
+                ));
+            }
+            stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        }
+        self.nodes.push(node);
+
+        Ok(id)
+    }
+
+    fn check_output(&self, node: NodeId) -> Result<Option<NodeId>, JsValue> {
+        if (node as usize) < self.nodes.len() {
+            Ok(Some(node))
+        } else {
+            Err(JsValue::from_str(&format!("No shader node with id {node}")))
+        }
+    }
+
+    pub(crate) fn outputs(&self) -> &ShaderOutputs {
+        &self.outputs
+    }
+
+    // Method to get the values of the nodes up to the last output, textures are looked up with the function.
+    // Inputs always come before the nodes that use them, so every node is evaluated once in order
+    pub(crate) fn evaluate(
+        &self,
+        context: &ShaderContext,
+        texture: &dyn Fn(TextureId, &TextureQuery) -> Vector,
+    ) -> ShaderValues {
+        let count = self
+            .outputs
+            .nodes()
+            .into_iter()
+            .flatten()
+            .map(|node| node as usize + 1)
+            .max()
+            .unwrap_or(0);
+
+        let mut values = [Vector::default(); MAX_NODES];
+        for (id, node) in self.nodes[..count].iter().enumerate() {
+            values[id] = Shader::evaluate_node(node, &values[..id], context, texture);
+        }
+
+        ShaderValues { values, count }
+    }
+
+    fn evaluate_node(
+        node: &ShaderNode,
+        values: &[Vector],
+        context: &ShaderContext,
+        texture: &dyn Fn(TextureId, &TextureQuery) -> Vector,
+    ) -> Vector {
+        let input = |id: NodeId| values[id as usize];
+        let gray = |value: f64| Vector::new(value, value, value);
+        let uv_of = |id: &Option<NodeId>| {
+            id.map_or(context.query.uv, |id| {
+                let uv = input(id);
+                (uv.x, uv.y)
+            })
+        };
+
+        match node {
+            ShaderNode::Constant { value } => *value,
+            ShaderNode::Uv => Vector::new(context.query.uv.0, context.query.uv.1, 0.0),
+            ShaderNode::Position => context.query.point,
+            ShaderNode::Normal => context.normal,
+            ShaderNode::FacingRatio => {
+                gray(context.normal.dot(&context.direction.normalize()).abs())
+            }
+            ShaderNode::Fresnel { ior } => {
+                let cos_theta = context.normal.dot(&context.direction.normalize()).abs();
+                let r0 = ((1.0 - ior) / (1.0 + ior)).powi(2);
+
+                gray(r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5))
+            }
+            ShaderNode::Texture { texture: id, uv } => {
+                let query = TextureQuery {
+                    uv: uv_of(uv),
+                    ..context.query
+                };
+
+                texture(*id, &query)
+            }
+            ShaderNode::UvTransform {
+                uv,
+                scale,
+                rotation,
+                offset,
+            } => {
+                // Scale, then rotate around the origin, then move
+                let (u, v) = uv_of(uv);
+                let (u, v) = (u * scale.0, v * scale.1);
+                let (sin, cos) = rotation.to_radians().sin_cos();
+
+                Vector::new(
+                    u * cos - v * sin + offset.0,
+                    u * sin + v * cos + offset.1,
+                    0.0,
+                )
+            }
+            ShaderNode::Math { operation, a, b } => {
+                let (a, b) = (input(*a), input(*b));
+
+                Vector::new(
+                    operation.apply(a.x, b.x),
+                    operation.apply(a.y, b.y),
+                    operation.apply(a.z, b.z),
+                )
+            }
+            ShaderNode::Mix { a, b, factor } => {
+                let t = input(*factor);
+
+                input(*a) * (Vector::new(1.0, 1.0, 1.0) - t) + input(*b) * t
+            }
+            ShaderNode::ColorRamp { input: id, stops } => {
+                let t = input(*id).x;
+                let (Some(first), Some(last)) = (stops.first(), stops.last()) else {
+                    return gray(t);
+                };
+                if t <= first.position {
+                    return first.color;
+                }
+
+                // Blend between the stops on both sides of the value
+                stops
+                    .windows(2)
+                    .find(|pair| t <= pair[1].position)
+                    .map_or(last.color, |pair| {
+                        let width = pair[1].position - pair[0].position;
+                        let f = if width > 0.0 {
+                            (t - pair[0].position) / width
+                        } else {
+                            1.0
+                        };
+                        pair[0].color * (1.0 - f) + pair[1].color * f
+                    })
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Shader {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Shader {
+        init_panic_hook();
+
+        Shader::default()
+    }
+
+    pub fn from_json(json: &str) -> Result<Shader, JsValue> {
+        init_panic_hook();
+
+        let shader: Shader = serde_json::from_str(json)
+            .map_err(|error| JsValue::from_str(&format!("Could not read the shader: {error}")))?;
+
+        // Check the graph the same way as when it is built node by node
+        let mut checked = Shader::new();
+        for node in shader.nodes {
+            checked.add_node(node)?;
+        }
+        for output in shader.outputs.nodes().into_iter().flatten() {
+            checked.check_output(output)?;
+        }
+        checked.outputs = shader.outputs;
+
+        Ok(checked)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn constant(&mut self, value: Vector) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Constant { value })
+    }
+
+    pub fn value(&mut self, value: f64) -> Result<NodeId, JsValue> {
+        self.constant(Vector::new(value, value, value))
+    }
+
+    pub fn uv(&mut self) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Uv)
+    }
+
+    pub fn position(&mut self) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Position)
+    }
+
+    pub fn normal(&mut self) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Normal)
+    }
+
+    pub fn facing_ratio(&mut self) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::FacingRatio)
+    }
+
+    pub fn fresnel(&mut self, ior: f64) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Fresnel { ior })
+    }
+
+    // Texture from the scene, at the texture coordinates of another node or of the hit
+    pub fn texture(&mut self, texture: TextureId, uv: Option<NodeId>) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Texture { texture, uv })
+    }
+
+    pub fn uv_transform(
+        &mut self,
+        uv: Option<NodeId>,
+        scale_u: f64,
+        scale_v: f64,
+        rotation: f64,
+        offset_u: f64,
+        offset_v: f64,
+    ) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::UvTransform {
+            uv,
+            scale: (scale_u, scale_v),
+            rotation,
+            offset: (offset_u, offset_v),
+        })
+    }
+
+    pub fn math(
+        &mut self,
+        operation: MathOperation,
+        a: NodeId,
+        b: NodeId,
+    ) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Math { operation, a, b })
+    }
+
+    pub fn mix(&mut self, a: NodeId, b: NodeId, factor: NodeId) -> Result<NodeId, JsValue> {
+        self.add_node(ShaderNode::Mix { a, b, factor })
+    }
+
+    // Stops are given as positions with one color for every position
+    pub fn color_ramp(
+        &mut self,
+        input: NodeId,
+        positions: Vec<f64>,
+        colors: Vec<Vector>,
+    ) -> Result<NodeId, JsValue> {
+        if positions.len() != colors.len() {
+            return Err(JsValue::from_str(
+                "A color ramp needs one color for every position",
+            ));
+        }
+
+        let stops = positions
+            .into_iter()
+            .zip(colors)
+            .map(|(position, color)| ColorStop { position, color })
+            .collect();
+
+        self.add_node(ShaderNode::ColorRamp { input, stops })
+    }
+
+    pub fn set_color(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.color = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_roughness(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.roughness = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_emission(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.emission = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_metallic(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.metallic = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_specular(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.specular = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_anisotropy(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.anisotropy = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_clearcoat(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.clearcoat = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_transmission(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.transmission = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_sheen(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.sheen = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    pub fn set_subsurface(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.subsurface = self.check_output(node)?;
+
+        Ok(())
+    }
+
+    // The node gives the normal along u, along v and out of the surface like the texels of a normal map
+    pub fn set_normal(&mut self, node: NodeId) -> Result<(), JsValue> {
+        self.outputs.normal = self.check_output(node)?;
+
+        Ok(())
+    }
+}
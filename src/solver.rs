@@ -0,0 +1,279 @@
+use std::ops::Deref;
+
+// Solvers for the polynomial equations of the analytic primitives
+
+// Rust Roots struct, up to four real roots kept in place so that solving never allocates
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Roots {
+    values: [f64; 4],
+    len: usize,
+}
+
+impl Roots {
+    fn push(&mut self, root: f64) {
+        self.values[self.len] = root;
+        self.len += 1;
+    }
+
+    pub fn map(mut self, f: impl Fn(f64) -> f64) -> Roots {
+        for root in self.values[..self.len].iter_mut() {
+            *root = f(*root);
+        }
+
+        self
+    }
+}
+
+impl Deref for Roots {
+    type Target = [f64];
+
+    fn deref(&self) -> &[f64] {
+        &self.values[..self.len]
+    }
+}
+
+impl From<Option<(f64, f64)>> for Roots {
+    fn from(pair: Option<(f64, f64)>) -> Roots {
+        let mut roots = Roots::default();
+        if let Some((first, second)) = pair {
+            roots.push(first);
+            roots.push(second);
+        }
+
+        roots
+    }
+}
+
+// Real roots of a * x^2 + b * x + c = 0 in ascending order
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let root = -c / b;
+        return Some((root, root));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    // Numerically stable form that avoids subtracting two nearly equal numbers
+    let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+    let (root_1, root_2) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+
+    Some((root_1.min(root_2), root_1.max(root_2)))
+}
+
+// Real roots of x^3 + a * x^2 + b * x + c = 0
+fn solve_cubic(a: f64, b: f64, c: f64) -> Roots {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+    let shift = a / 3.0;
+    let mut roots = Roots::default();
+
+    if r * r < q * q * q {
+        // Three real roots
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let m = -2.0 * q.sqrt();
+        for offset in [0.0, 2.0 * std::f64::consts::PI, -2.0 * std::f64::consts::PI] {
+            roots.push(m * ((theta + offset) / 3.0).cos() - shift);
+        }
+    } else {
+        // One real root
+        let s = -(r.abs() + (r * r - q * q * q).sqrt()).cbrt().copysign(r);
+        let t = if s == 0.0 { 0.0 } else { q / s };
+        roots.push(s + t - shift);
+    }
+
+    roots
+}
+
+// Real roots of a * x^4 + b * x^3 + c * x^2 + d * x + e = 0 in ascending order
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Roots {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // A leading coefficient that is tiny next to the others puts one root so far away that the normalized
+    // coefficients lose the rest. Those are then close to the roots of the cubic without the leading term,
+    // and the far root follows from the sum of all roots
+    let mut roots = if b * b > 1e3 * (b.abs() + c.abs().max(d.abs()).max(e.abs())) {
+        let mut roots = solve_cubic(c / b, d / b, e / b);
+        roots.push(c / b - b);
+        roots
+    } else {
+        solve_monic_quartic(b, c, d, e)
+    };
+
+    // Polish the roots with a few Newton iterations on the original polynomial
+    for root in roots.values[..roots.len].iter_mut() {
+        for _ in 0..2 {
+            let x = *root;
+            let value = (((x + b) * x + c) * x + d) * x + e;
+            let derivative = ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+            if derivative != 0.0 {
+                *root = x - value / derivative;
+            }
+        }
+    }
+
+    roots.values[..roots.len].sort_unstable_by(|x, y| x.total_cmp(y));
+    roots
+}
+
+// Real roots of x^4 + b * x^3 + c * x^2 + d * x + e = 0 in no particular order
+fn solve_monic_quartic(b: f64, c: f64, d: f64, e: f64) -> Roots {
+    // Depress the quartic with x = y - b / 4
+    let shift = b / 4.0;
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut roots = Roots::default();
+
+    if q.abs() < 1e-12 {
+        // Biquadratic equation y^4 + p * y^2 + r = 0
+        if let Some((z_1, z_2)) = solve_quadratic(1.0, p, r) {
+            for z in [z_1, z_2] {
+                if z >= 0.0 {
+                    roots.push(z.sqrt() - shift);
+                    roots.push(-z.sqrt() - shift);
+                }
+            }
+        }
+    } else {
+        // Ferrari's method with the largest root of the resolvent cubic
+        let m = solve_cubic(2.0 * p, p * p - 4.0 * r, -q * q)
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        if m > 0.0 {
+            let sqrt_m = m.sqrt();
+            let half_q = q / (2.0 * sqrt_m);
+            // Factor into (y^2 + sqrt_m * y + t) * (y^2 - sqrt_m * y + v)
+            for (linear, constant) in [
+                (sqrt_m, (p + m) / 2.0 - half_q),
+                (-sqrt_m, (p + m) / 2.0 + half_q),
+            ] {
+                if let Some((y_1, y_2)) = solve_quadratic(1.0, linear, constant) {
+                    roots.push(y_1 - shift);
+                    roots.push(y_2 - shift);
+                }
+            }
+        }
+    }
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(roots: &[f64], expected: &[f64], tolerance: f64) {
+        assert_eq!(
+            roots.len(),
+            expected.len(),
+            "expected {expected:?} but got {roots:?}"
+        );
+        for (root, expected_root) in roots.iter().zip(expected) {
+            assert!(
+                (root - expected_root).abs() <= tolerance * expected_root.abs().max(1.0),
+                "expected {expected:?} but got {roots:?}"
+            );
+        }
+    }
+
+    fn sorted(roots: Roots) -> Vec<f64> {
+        let mut roots = roots.to_vec();
+        roots.sort_by(|x, y| x.total_cmp(y));
+        roots
+    }
+
+    #[test]
+    fn quadratic_roots() {
+        let pair = |roots: Option<(f64, f64)>| roots.map(|(x, y)| vec![x, y]).unwrap_or_default();
+
+        assert_roots(&pair(solve_quadratic(1.0, -4.0, 3.0)), &[1.0, 3.0], 1e-12);
+        assert_roots(&pair(solve_quadratic(-2.0, 8.0, -6.0)), &[1.0, 3.0], 1e-12);
+        assert_roots(&pair(solve_quadratic(1.0, -4.0, 4.0)), &[2.0, 2.0], 1e-12);
+        assert_eq!(solve_quadratic(1.0, 0.0, 1.0), None);
+
+        // Without the square term the single root is given twice
+        assert_roots(&pair(solve_quadratic(0.0, 2.0, -4.0)), &[2.0, 2.0], 1e-12);
+        assert_eq!(solve_quadratic(0.0, 0.0, 1.0), None);
+
+        // A tiny square term leaves the small root exact and puts the other far away
+        assert_roots(
+            &pair(solve_quadratic(1e-20, 1.0, -1.0)),
+            &[-1e20, 1.0],
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn cubic_roots() {
+        assert_roots(
+            &sorted(solve_cubic(-6.0, 11.0, -6.0)),
+            &[1.0, 2.0, 3.0],
+            1e-12,
+        );
+        assert_roots(&sorted(solve_cubic(-3.0, 3.0, -1.0)), &[1.0], 1e-12);
+        assert_roots(&sorted(solve_cubic(0.0, 0.0, -8.0)), &[2.0], 1e-12);
+        assert_roots(&sorted(solve_cubic(0.0, 1.0, 0.0)), &[0.0], 1e-12);
+    }
+
+    #[test]
+    fn quartic_roots() {
+        // (x - 1) * (x - 2) * (x - 3) * (x - 4)
+        assert_roots(
+            &solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0),
+            &[1.0, 2.0, 3.0, 4.0],
+            1e-9,
+        );
+        assert_roots(
+            &solve_quartic(-1e-6, 1e-5, -3.5e-5, 5e-5, -2.4e-5),
+            &[1.0, 2.0, 3.0, 4.0],
+            1e-9,
+        );
+
+        // Repeated roots are only as exact as the square root of the precision
+        assert_roots(
+            &solve_quartic(1.0, -8.0, 22.0, -24.0, 9.0),
+            &[1.0, 1.0, 3.0, 3.0],
+            1e-6,
+        );
+        assert_roots(&solve_quartic(1.0, -4.0, 6.0, -4.0, 1.0), &[1.0; 4], 1e-3);
+
+        // (x^2 - 1) * (x^2 - 4) has no odd terms
+        assert_roots(
+            &solve_quartic(1.0, 0.0, -5.0, 0.0, 4.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+            1e-12,
+        );
+        assert_roots(&solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[], 0.0);
+        assert_roots(
+            &solve_quartic(1.0, 0.0, 1.0, 0.0, -2.0),
+            &[-1.0, 1.0],
+            1e-12,
+        );
+    }
+
+    #[test]
+    fn quartic_with_tiny_leading_coefficient() {
+        // 1e-9 * x^4 + (x - 1) * (x - 2) * (x - 3) keeps the roots of the cubic and adds one far away
+        assert_roots(
+            &solve_quartic(1e-9, 1.0, -6.0, 11.0, -6.0),
+            &[-1e9 - 6.0, 1.0, 2.0, 3.0],
+            1e-6,
+        );
+
+        // (x - 1e5) * (x - 1) * (x - 2) * (x - 3)
+        assert_roots(
+            &solve_quartic(1.0, -100006.0, 600011.0, -1100006.0, 600000.0),
+            &[1.0, 2.0, 3.0, 1e5],
+            1e-9,
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use std::sync::OnceLock;
+
+use crate::vector::Vector;
+
+// Range of wavelengths in nanometers that paths are traced at in spectral mode
+const MIN_WAVELENGTH: f64 = 380.0;
+const MAX_WAVELENGTH: f64 = 780.0;
+const INTEGRATION_STEP: f64 = 5.0;
+
+// Spectra of Smits that colors are built from, in 10 bins from 380 to 720 nanometers
+const SMITS_BINS: usize = 10;
+const SMITS_WHITE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f64; SMITS_BINS] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f64; SMITS_BINS] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f64; SMITS_BINS] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f64; SMITS_BINS] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f64; SMITS_BINS] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+// Linear sRGB from CIE XYZ
+const XYZ_TO_RGB: [[f64; 3]; 3] = [
+    [3.2404542, -1.5371385, -0.4985314],
+    [-0.9692660, 1.8760108, 0.0415560],
+    [0.0556434, -0.2040259, 1.0572252],
+];
+
+// Wavelengths in nanometers that the red, green and blue channels stand for when they are interpolated
+const CHANNEL_WAVELENGTHS: [f64; 3] = [650.0, 550.0, 450.0];
+
+// Rust Wavelengths struct, the wavelengths a path carries in spectral mode, one per channel of its colors
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Wavelengths {
+    pub nanometers: [f64; 3], // The first is the hero wavelength, the others are spread evenly from it
+    pub hero_only: bool,      // Set once the other wavelengths are dropped from the path
+}
+
+impl Wavelengths {
+    // Method to pick the hero wavelength from a random value between 0 and 1
+    pub fn sample(u: f64) -> Wavelengths {
+        let range = MAX_WAVELENGTH - MIN_WAVELENGTH;
+        let hero = u.clamp(0.0, 1.0) * range;
+        let rotated = |i: f64| MIN_WAVELENGTH + (hero + i * range / 3.0) % range;
+
+        Wavelengths {
+            nanometers: [rotated(0.0), rotated(1.0), rotated(2.0)],
+            hero_only: false,
+        }
+    }
+
+    pub fn hero(&self) -> f64 {
+        self.nanometers[0]
+    }
+
+    // Method to drop all but the hero wavelength, the weight of the path is moved to it
+    pub fn terminate_secondary(&mut self, weight: &mut Vector) {
+        if !self.hero_only {
+            *weight = Vector::new(weight.x * 3.0, 0.0, 0.0);
+            self.hero_only = true;
+        }
+    }
+
+    fn map(&self, f: impl Fn(f64) -> f64) -> Vector {
+        Vector::new(
+            f(self.nanometers[0]),
+            f(self.nanometers[1]),
+            f(self.nanometers[2]),
+        )
+    }
+
+    // Method to get a smooth spectrum for the RGB color at the wavelengths, reflectances stay below about 1
+    pub fn upsample(&self, rgb: &Vector) -> Vector {
+        let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+
+        // The smallest channel is white, the next one a secondary color and the rest a primary
+        let [(first, a), (second, c), (third, d)] = if r <= g && r <= b {
+            if g <= b {
+                [
+                    (&SMITS_WHITE, r),
+                    (&SMITS_CYAN, g - r),
+                    (&SMITS_BLUE, b - g),
+                ]
+            } else {
+                [
+                    (&SMITS_WHITE, r),
+                    (&SMITS_CYAN, b - r),
+                    (&SMITS_GREEN, g - b),
+                ]
+            }
+        } else if g <= r && g <= b {
+            if r <= b {
+                [
+                    (&SMITS_WHITE, g),
+                    (&SMITS_MAGENTA, r - g),
+                    (&SMITS_BLUE, b - r),
+                ]
+            } else {
+                [
+                    (&SMITS_WHITE, g),
+                    (&SMITS_MAGENTA, b - g),
+                    (&SMITS_RED, r - b),
+                ]
+            }
+        } else if r <= g {
+            [
+                (&SMITS_WHITE, b),
+                (&SMITS_YELLOW, r - b),
+                (&SMITS_GREEN, g - r),
+            ]
+        } else {
+            [
+                (&SMITS_WHITE, b),
+                (&SMITS_YELLOW, g - b),
+                (&SMITS_RED, r - g),
+            ]
+        };
+
+        self.map(|wavelength| {
+            (a * smits(first, wavelength)
+                + c * smits(second, wavelength)
+                + d * smits(third, wavelength))
+            .max(0.0)
+        })
+    }
+
+    // Method to get values given for red, green and blue, like an index of refraction, at the wavelengths
+    pub fn interpolate(&self, rgb: &Vector) -> Vector {
+        let [red, green, blue] = CHANNEL_WAVELENGTHS;
+
+        self.map(|wavelength| {
+            if wavelength <= blue {
+                rgb.z
+            } else if wavelength <= green {
+                rgb.z + (rgb.y - rgb.z) * (wavelength - blue) / (green - blue)
+            } else if wavelength <= red {
+                rgb.y + (rgb.x - rgb.y) * (wavelength - green) / (red - green)
+            } else {
+                rgb.x
+            }
+        })
+    }
+
+    // Method to get the light of a black body at the temperature in Kelvin, as bright as white
+    pub fn blackbody(&self, temperature: f64) -> Vector {
+        let luminance = blackbody_luminance(temperature);
+        if luminance <= 0.0 {
+            return Vector::default();
+        }
+
+        self.map(|wavelength| planck(wavelength, temperature) / luminance)
+    }
+
+    // Method to turn the values of a path at the wavelengths into a linear RGB color, white stays white
+    pub fn rgb(&self, values: &Vector) -> Vector {
+        // Every wavelength is picked evenly from the range
+        let range = MAX_WAVELENGTH - MIN_WAVELENGTH;
+        let xyz = [values.x, values.y, values.z]
+            .iter()
+            .zip(self.nanometers)
+            .fold(Vector::default(), |xyz, (value, wavelength)| {
+                xyz + cie_xyz(wavelength) * (value * range / 3.0)
+            });
+
+        xyz_to_rgb(&xyz) / xyz_to_rgb(white())
+    }
+}
+
+// Method to get the RGB color of a black body at the temperature in Kelvin, as seen in spectral mode
+pub(crate) fn blackbody_rgb(temperature: f64) -> Vector {
+    let xyz = integrate(|wavelength| planck(wavelength, temperature));
+    if xyz.y <= 0.0 {
+        return Vector::default();
+    }
+
+    xyz_to_rgb(&(xyz / (xyz.y / white().y))) / xyz_to_rgb(white())
+}
+
+// Index of refraction of glass at the wavelength in nanometers from Cauchy's equation, ior is at the yellow
+// helium line and the abbe number sets how much it changes
+pub(crate) fn dispersed_ior(ior: f64, abbe_number: f64, wavelength: f64) -> f64 {
+    if abbe_number <= 0.0 {
+        return ior;
+    }
+
+    // Fraunhofer lines F and C that the abbe number is measured between, and d
+    let (f, c, d) = (486.13_f64, 656.27_f64, 587.56_f64);
+    let b = (ior - 1.0) / (abbe_number * (f.powi(-2) - c.powi(-2)));
+    let a = ior - b / (d * d);
+
+    a + b / (wavelength * wavelength)
+}
+
+// Value of a spectrum of Smits at the wavelength, linear between the middles of the bins
+fn smits(table: &[f64; SMITS_BINS], wavelength: f64) -> f64 {
+    let width = (720.0 - MIN_WAVELENGTH) / SMITS_BINS as f64;
+    let position =
+        ((wavelength - MIN_WAVELENGTH) / width - 0.5).clamp(0.0, (SMITS_BINS - 1) as f64);
+    let bin = (position as usize).min(SMITS_BINS - 2);
+    let t = position - bin as f64;
+
+    table[bin] * (1.0 - t) + table[bin + 1] * t
+}
+
+// Spectral radiance of a black body from Planck's law
+fn planck(wavelength: f64, temperature: f64) -> f64 {
+    if temperature <= 0.0 {
+        return 0.0;
+    }
+
+    let (h, c, k) = (6.62607015e-34, 2.99792458e8, 1.380649e-23);
+    let meters = wavelength * 1e-9;
+    2.0 * h * c * c / (meters.powi(5) * ((h * c / (meters * k * temperature)).exp() - 1.0))
+}
+
+// Luminance of a black body over the luminance of the flat spectrum of 1
+fn blackbody_luminance(temperature: f64) -> f64 {
+    integrate(|wavelength| planck(wavelength, temperature)).y / white().y
+}
+
+// CIE 1931 color matching functions from the multi-lobe fit of Wyman et al.
+fn cie_xyz(wavelength: f64) -> Vector {
+    let lobe = |mean: f64, below: f64, above: f64| {
+        let t = (wavelength - mean) / if wavelength < mean { below } else { above };
+        (-0.5 * t * t).exp()
+    };
+
+    Vector::new(
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+fn xyz_to_rgb(xyz: &Vector) -> Vector {
+    let row = |row: &[f64; 3]| row[0] * xyz.x + row[1] * xyz.y + row[2] * xyz.z;
+
+    Vector::new(
+        row(&XYZ_TO_RGB[0]),
+        row(&XYZ_TO_RGB[1]),
+        row(&XYZ_TO_RGB[2]),
+    )
+}
+
+// XYZ of a spectrum over the range, summed in steps of a few nanometers
+fn integrate(spectrum: impl Fn(f64) -> f64) -> Vector {
+    let steps = ((MAX_WAVELENGTH - MIN_WAVELENGTH) / INTEGRATION_STEP) as u32;
+
+    (0..steps).fold(Vector::default(), |xyz, step| {
+        let wavelength = MIN_WAVELENGTH + (step as f64 + 0.5) * INTEGRATION_STEP;
+        xyz + cie_xyz(wavelength) * (spectrum(wavelength) * INTEGRATION_STEP)
+    })
+}
+
+// XYZ of the flat spectrum of 1, every color is divided by its RGB so that it comes out white
+fn white() -> &'static Vector {
+    static WHITE: OnceLock<Vector> = OnceLock::new();
+
+    WHITE.get_or_init(|| integrate(|_| 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mean color of a spectrum over hero wavelengths spread evenly across the range
+    fn mean_rgb(rgb: &Vector, hero_only: bool) -> Vector {
+        let count = 10_000;
+        let mut total = Vector::default();
+        for i in 0..count {
+            let mut wavelengths = Wavelengths::sample((i as f64 + 0.5) / count as f64);
+            let mut values = wavelengths.upsample(rgb);
+            if hero_only {
+                wavelengths.terminate_secondary(&mut values);
+            }
+            total += wavelengths.rgb(&values);
+        }
+
+        total / count as f64
+    }
+
+    #[test]
+    fn white_comes_back_white() {
+        let white = Vector::new(1.0, 1.0, 1.0);
+        for hero_only in [false, true] {
+            let rgb = mean_rgb(&white, hero_only);
+            assert!((rgb - white).magnitude() < 0.01, "{rgb:?}");
+        }
+    }
+}
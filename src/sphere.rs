@@ -1,10 +1,15 @@
+use std::f64::consts::PI;
+
 use wasm_bindgen::prelude::*;
 
 use crate::{
+    aabb::Aabb,
+    csg::Solid,
     init_panic_hook,
-    intersection::{Intersectable, Intersection},
+    intersection::Intersectable,
     material::Material,
     ray::Ray,
+    solver::{solve_quadratic, Roots},
     vector::Vector,
 };
 
@@ -18,33 +23,36 @@ pub struct Sphere {
 }
 
 impl Intersectable for Sphere {
-    fn intersect(&self, ray: &Ray) -> Option<Intersection> {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.dot(&ray.direction);
-        let b = oc.dot(&ray.direction) * 2.0;
-        let c = oc.dot(&oc) - self.radius * self.radius;
-        let discriminant = b * b - 4.0 * a * c;
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        // The nearest of the two crossings within the ray, the far one if the ray starts inside
+        self.crossings(ray)
+            .iter()
+            .copied()
+            .find(|t| ray.contains(*t))
+    }
 
-        if discriminant >= 0.0 {
-            // Ray intersects the sphere, calculate intersection point
-            let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        (*point - self.center).normalize()
+    }
 
-            if t > 0.0 {
-                let intersection_point = ray.point_at_parameter(t);
-                return Some(Intersection {
-                    t,
-                    intersection_point,
-                    intersection_object: Box::new(*self),
-                });
-            }
-        }
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // Spherical coordinates, v goes from the top of the sphere (negative y) to the bottom
+        let direction = self.calculate_normal(point);
+        let u = 0.5 + direction.z.atan2(direction.x) / (2.0 * PI);
+        let v = (-direction.y).clamp(-1.0, 1.0).acos() / PI;
 
-        // Ray does not intersect the sphere
-        None
+        (u, v)
     }
 
-    fn calculate_normal(&self, point: &Vector) -> Vector {
-        (*point - self.center).normalize()
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        spherical_tangents(&(*point - self.center))
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::from_center(
+            self.center,
+            Vector::new(self.radius, self.radius, self.radius),
+        )
     }
 
     fn get_material(&self) -> &Material {
@@ -52,6 +60,31 @@ impl Intersectable for Sphere {
     }
 }
 
+// Method to get how a point moves with the spherical coordinates used for the texture coordinates
+pub(crate) fn spherical_tangents(offset: &Vector) -> (Vector, Vector) {
+    // u turns around the y-axis and v goes from the top (negative y) to the bottom
+    let distance = (offset.x * offset.x + offset.z * offset.z).sqrt();
+    let dpdu = Vector::new(-offset.z, 0.0, offset.x) * (2.0 * PI);
+    let dpdv = Vector::new(
+        -offset.y * offset.x / distance,
+        distance,
+        -offset.y * offset.z / distance,
+    ) * PI;
+
+    (dpdu, dpdv)
+}
+
+impl Solid for Sphere {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        let oc = ray.origin - self.center;
+        Roots::from(solve_quadratic(
+            ray.direction.dot(&ray.direction),
+            2.0 * oc.dot(&ray.direction),
+            oc.dot(&oc) - self.radius * self.radius,
+        ))
+    }
+}
+
 #[wasm_bindgen]
 impl Sphere {
     #[wasm_bindgen(constructor)]
@@ -0,0 +1,460 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    init_panic_hook,
+    intersection::Intersection,
+    noise::{fbm, turbulence, worley},
+    vector::Vector,
+};
+
+// Handle of a texture in the scene
+pub type TextureId = u32;
+
+// Rust WrapMode enum, what happens to texture coordinates outside of 0 to 1
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+    Mirror,
+}
+
+// Rust TextureFilter enum
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TextureFilter {
+    Nearest,
+    Bilinear,
+    Trilinear, // Bilinear on the two closest mipmap levels
+}
+
+// Rust MipLevel struct, the texture at one resolution
+#[derive(Debug)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<[f32; 3]>,
+}
+
+impl MipLevel {
+    // Method to get the next level with half the resolution by averaging blocks of 2 by 2 texels
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 3];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let texel = self.texel(2 * x + dx, 2 * y + dy);
+                    for channel in 0..3 {
+                        sum[channel] += texel[channel] / 4.0;
+                    }
+                }
+                texels.push(sum);
+            }
+        }
+
+        MipLevel {
+            width,
+            height,
+            texels,
+        }
+    }
+
+    fn texel(&self, x: usize, y: usize) -> [f32; 3] {
+        self.texels[y.min(self.height - 1) * self.width + x.min(self.width - 1)]
+    }
+}
+
+// Method to bring a texel coordinate that may be outside of the texture back in
+fn wrap(coordinate: i64, size: usize, mode: WrapMode) -> usize {
+    let size = size as i64;
+
+    match mode {
+        WrapMode::Repeat => coordinate.rem_euclid(size) as usize,
+        WrapMode::Clamp => coordinate.clamp(0, size - 1) as usize,
+        WrapMode::Mirror => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size {
+                period as usize
+            } else {
+                (2 * size - 1 - period) as usize
+            }
+        }
+    }
+}
+
+// Rust TextureNode enum, an image or a pattern, patterns can be built out of other textures
+#[derive(Debug)]
+enum TextureNode {
+    Image(Vec<MipLevel>),
+    Constant(Vector),
+    Checker {
+        even: Texture,
+        odd: Texture,
+        scale: f64,
+        solid: bool, // Checks in space instead of on the texture coordinates
+    },
+    Gradient {
+        from: Texture,
+        to: Texture,
+        start: Vector,
+        end: Vector,
+    },
+    Noise {
+        scale: f64,
+        octaves: u32,
+    },
+    Turbulence {
+        scale: f64,
+        octaves: u32,
+    },
+    Worley {
+        scale: f64,
+    },
+    Marble {
+        scale: f64,
+        octaves: u32,
+        strength: f64,
+    },
+    Wood {
+        scale: f64,
+        rings: f64,
+    },
+    Mix {
+        a: Texture,
+        b: Texture,
+        factor: Texture,
+    },
+    Multiply {
+        a: Texture,
+        b: Texture,
+    },
+}
+
+// Rust TextureQuery struct, where a texture is looked up
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct TextureQuery {
+    pub uv: (f64, f64),
+    pub point: Vector,
+    pub footprint: (f64, f64), // Size of the area that is seen, in texture coordinates along u and v
+}
+
+impl TextureQuery {
+    // Method to look up textures at a hit, the cone width is how wide the ray is there
+    pub fn new(intersection: &Intersection, cone_width: f64) -> TextureQuery {
+        TextureQuery {
+            uv: intersection.uv,
+            point: intersection.intersection_point,
+            footprint: (
+                cone_width / intersection.dpdu.magnitude(),
+                cone_width / intersection.dpdv.magnitude(),
+            ),
+        }
+    }
+
+    // Method to look up a little further along the surface
+    pub fn offset(&self, du: f64, dv: f64, dpdu: &Vector, dpdv: &Vector) -> TextureQuery {
+        TextureQuery {
+            uv: (self.uv.0 + du, self.uv.1 + dv),
+            point: self.point + *dpdu * du + *dpdv * dv,
+            ..*self
+        }
+    }
+}
+
+// Rust Texture struct, an image with its mipmaps or a procedural pattern
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Texture {
+    node: Rc<TextureNode>,
+    pub wrap: WrapMode, // Only used by images
+    pub filter: TextureFilter,
+}
+
+impl Texture {
+    fn from_node(node: TextureNode) -> Texture {
+        init_panic_hook();
+
+        Texture {
+            node: Rc::new(node),
+            wrap: WrapMode::Repeat,
+            filter: TextureFilter::Trilinear,
+        }
+    }
+
+    fn from_texels(width: usize, height: usize, texels: Vec<[f32; 3]>) -> Texture {
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            texels,
+        }];
+        while levels
+            .last()
+            .is_some_and(|level| level.width > 1 || level.height > 1)
+        {
+            let next = levels[levels.len() - 1].downsample();
+            levels.push(next);
+        }
+
+        Texture::from_node(TextureNode::Image(levels))
+    }
+
+    fn nearest(&self, level: &MipLevel, u: f64, v: f64) -> Vector {
+        let x = wrap(
+            (u * level.width as f64).floor() as i64,
+            level.width,
+            self.wrap,
+        );
+        let y = wrap(
+            (v * level.height as f64).floor() as i64,
+            level.height,
+            self.wrap,
+        );
+        let [r, g, b] = level.texel(x, y);
+
+        Vector::new(r as f64, g as f64, b as f64)
+    }
+
+    fn bilinear(&self, level: &MipLevel, u: f64, v: f64) -> Vector {
+        // Texel centers are at half coordinates
+        let x = u * level.width as f64 - 0.5;
+        let y = v * level.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let texel = |dx: i64, dy: i64| {
+            let [r, g, b] = level.texel(
+                wrap(x0 as i64 + dx, level.width, self.wrap),
+                wrap(y0 as i64 + dy, level.height, self.wrap),
+            );
+            Vector::new(r as f64, g as f64, b as f64)
+        };
+
+        (texel(0, 0) * (1.0 - fx) + texel(1, 0) * fx) * (1.0 - fy)
+            + (texel(0, 1) * (1.0 - fx) + texel(1, 1) * fx) * fy
+    }
+
+    fn sample_image(&self, levels: &[MipLevel], query: &TextureQuery) -> Vector {
+        // v goes down the image
+        let (u, v) = query.uv;
+
+        match self.filter {
+            TextureFilter::Nearest => self.nearest(&levels[0], u, v),
+            TextureFilter::Bilinear => self.bilinear(&levels[0], u, v),
+            TextureFilter::Trilinear => {
+                // Pick the level where a texel is about as big as the footprint
+                let texels = (query.footprint.0 * levels[0].width as f64)
+                    .max(query.footprint.1 * levels[0].height as f64);
+                let last = (levels.len() - 1) as f64;
+                let level = texels.max(1.0).log2().clamp(0.0, last);
+                let lower = level.floor() as usize;
+                let upper = level.ceil() as usize;
+                let fraction = level - lower as f64;
+
+                let color = self.bilinear(&levels[lower], u, v);
+                if upper == lower {
+                    color
+                } else {
+                    color * (1.0 - fraction) + self.bilinear(&levels[upper], u, v) * fraction
+                }
+            }
+        }
+    }
+
+    // Method to get the color of the texture, patterns give a gray value between 0 and 1
+    pub(crate) fn sample(&self, query: &TextureQuery) -> Vector {
+        let gray = |value: f64| Vector::new(value, value, value);
+
+        match self.node.as_ref() {
+            TextureNode::Image(levels) => self.sample_image(levels, query),
+            TextureNode::Constant(color) => *color,
+            TextureNode::Checker {
+                even,
+                odd,
+                scale,
+                solid,
+            } => {
+                let (u, v) = query.uv;
+                let cells = if *solid {
+                    let point = query.point * *scale;
+                    point.x.floor() + point.y.floor() + point.z.floor()
+                } else {
+                    (u * scale).floor() + (v * scale).floor()
+                };
+
+                if cells.rem_euclid(2.0) == 0.0 {
+                    even.sample(query)
+                } else {
+                    odd.sample(query)
+                }
+            }
+            TextureNode::Gradient {
+                from,
+                to,
+                start,
+                end,
+            } => {
+                let direction = *end - *start;
+                let t = ((query.point - *start).dot(&direction) / direction.dot(&direction))
+                    .clamp(0.0, 1.0);
+
+                from.sample(query) * (1.0 - t) + to.sample(query) * t
+            }
+            TextureNode::Noise { scale, octaves } => {
+                gray(0.5 + 0.5 * fbm(&(query.point * *scale), *octaves))
+            }
+            TextureNode::Turbulence { scale, octaves } => {
+                gray(turbulence(&(query.point * *scale), *octaves))
+            }
+            TextureNode::Worley { scale } => gray(worley(&(query.point * *scale)).min(1.0)),
+            TextureNode::Marble {
+                scale,
+                octaves,
+                strength,
+            } => {
+                // Bands along x that are bent by turbulence
+                let point = query.point * *scale;
+                let bands = (point.x + strength * turbulence(&point, *octaves)).sin();
+
+                gray(0.5 + 0.5 * bands)
+            }
+            TextureNode::Wood { scale, rings } => {
+                // Rings around the y-axis, a little noise makes them uneven
+                let point = query.point * *scale;
+                let distance = (point.x * point.x + point.z * point.z).sqrt();
+                let rings = distance * rings + 0.5 * fbm(&point, 2);
+
+                gray(rings - rings.floor())
+            }
+            TextureNode::Mix { a, b, factor } => {
+                let t = factor.sample(query);
+
+                a.sample(query) * (Vector::new(1.0, 1.0, 1.0) - t) + b.sample(query) * t
+            }
+            TextureNode::Multiply { a, b } => a.sample(query) * b.sample(query),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Texture {
+    // Decode a PNG, JPEG or Radiance HDR file. Colors are used as they are stored, like the canvas does
+    pub fn from_bytes(bytes: &[u8]) -> Result<Texture, JsValue> {
+        init_panic_hook();
+
+        let image = image::load_from_memory(bytes)
+            .map_err(|error| JsValue::from_str(&format!("Could not load the texture: {error}")))?
+            .into_rgb32f();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if width == 0 || height == 0 {
+            return Err(JsValue::from_str("A texture needs at least one texel"));
+        }
+
+        let texels = image.pixels().map(|pixel| pixel.0).collect();
+
+        Ok(Texture::from_texels(width, height, texels))
+    }
+
+    pub fn solid(color: Vector) -> Texture {
+        Texture::from_node(TextureNode::Constant(color))
+    }
+
+    // Checks on the texture coordinates, scale is the number of checks along u and v
+    pub fn checker(even: &Texture, odd: &Texture, scale: f64) -> Texture {
+        Texture::from_node(TextureNode::Checker {
+            even: even.clone(),
+            odd: odd.clone(),
+            scale,
+            solid: false,
+        })
+    }
+
+    // Checks in space, scale is the number of checks per unit
+    pub fn checker_3d(even: &Texture, odd: &Texture, scale: f64) -> Texture {
+        Texture::from_node(TextureNode::Checker {
+            even: even.clone(),
+            odd: odd.clone(),
+            scale,
+            solid: true,
+        })
+    }
+
+    // Blend in space from one texture at the start point to the other at the end point
+    pub fn gradient(from: &Texture, to: &Texture, start: Vector, end: Vector) -> Texture {
+        Texture::from_node(TextureNode::Gradient {
+            from: from.clone(),
+            to: to.clone(),
+            start,
+            end,
+        })
+    }
+
+    // Perlin noise with octaves of finer detail
+    pub fn noise(scale: f64, octaves: u32) -> Texture {
+        Texture::from_node(TextureNode::Noise { scale, octaves })
+    }
+
+    pub fn turbulence(scale: f64, octaves: u32) -> Texture {
+        Texture::from_node(TextureNode::Turbulence { scale, octaves })
+    }
+
+    // Cells around points scattered in space, dark at the points
+    pub fn worley(scale: f64) -> Texture {
+        Texture::from_node(TextureNode::Worley { scale })
+    }
+
+    pub fn marble(scale: f64, octaves: u32, strength: f64) -> Texture {
+        Texture::from_node(TextureNode::Marble {
+            scale,
+            octaves,
+            strength,
+        })
+    }
+
+    // Rings around the y-axis, rings is the number of rings per unit
+    pub fn wood(scale: f64, rings: f64) -> Texture {
+        Texture::from_node(TextureNode::Wood { scale, rings })
+    }
+
+    // Blend from this texture to the other by the factor, per channel
+    pub fn mix(&self, other: &Texture, factor: &Texture) -> Texture {
+        Texture::from_node(TextureNode::Mix {
+            a: self.clone(),
+            b: other.clone(),
+            factor: factor.clone(),
+        })
+    }
+
+    pub fn multiply(&self, other: &Texture) -> Texture {
+        Texture::from_node(TextureNode::Multiply {
+            a: self.clone(),
+            b: other.clone(),
+        })
+    }
+
+    pub fn scale(&self, factor: f64) -> Texture {
+        self.multiply(&Texture::solid(Vector::new(factor, factor, factor)))
+    }
+
+    // Size of an image in texels, zero for patterns
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        match self.node.as_ref() {
+            TextureNode::Image(levels) => levels[0].width as u32,
+            _ => 0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        match self.node.as_ref() {
+            TextureNode::Image(levels) => levels[0].height as u32,
+            _ => 0,
+        }
+    }
+}
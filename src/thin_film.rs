@@ -0,0 +1,151 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::{spectrum::Wavelengths, vector::Vector};
+
+// Wavelengths in nanometers averaged for the red, green and blue channels
+const WAVELENGTHS: [[f64; 4]; 3] = [
+    [612.5, 637.5, 662.5, 687.5],
+    [512.5, 537.5, 562.5, 587.5],
+    [412.5, 437.5, 462.5, 487.5],
+];
+
+// Rust Complex struct, just enough complex arithmetic for the Fresnel equations
+#[derive(Debug, Copy, Clone)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    fn new(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    fn norm_squared(&self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    // Principal square root
+    fn sqrt(&self) -> Complex {
+        let magnitude = self.norm_squared().sqrt();
+        let re = ((magnitude + self.re) / 2.0).max(0.0).sqrt();
+        let im = ((magnitude - self.re) / 2.0).max(0.0).sqrt();
+
+        Complex::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    // e to the power of i times the number
+    fn exp_i(&self) -> Complex {
+        let scale = (-self.im).exp();
+
+        Complex::new(scale * self.re.cos(), scale * self.re.sin())
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, c: Self) -> Self {
+        Complex::new(self.re + c.re, self.im + c.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, c: Self) -> Self {
+        Complex::new(self.re - c.re, self.im - c.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, c: Self) -> Self {
+        Complex::new(
+            self.re * c.re - self.im * c.im,
+            self.re * c.im + self.im * c.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, c: Self) -> Self {
+        let d = c.norm_squared();
+        if d == 0.0 {
+            return Complex::new(0.0, 0.0);
+        }
+
+        Complex::new(
+            (self.re * c.re + self.im * c.im) / d,
+            (self.im * c.re - self.re * c.im) / d,
+        )
+    }
+}
+
+// Rust ThinFilm struct, a layer a few hundred nanometers thick whose reflections interfere
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ThinFilm {
+    pub thickness: f64,                   // Nanometers
+    pub eta: f64, // Index of refraction of the film over the medium the light comes from
+    pub wavelengths: Option<Wavelengths>, // In spectral mode every channel is a single wavelength
+}
+
+impl ThinFilm {
+    // Method to get the reflectance of every channel of the film on a base with the complex index eta + ik
+    pub fn reflectance(&self, cos_theta: f64, base_eta: &Vector, base_k: &Vector) -> Vector {
+        if let Some(wavelengths) = self.wavelengths {
+            let [x, y, z] = wavelengths.nanometers;
+            return Vector::new(
+                self.airy(cos_theta, x, Complex::new(base_eta.x, base_k.x)),
+                self.airy(cos_theta, y, Complex::new(base_eta.y, base_k.y)),
+                self.airy(cos_theta, z, Complex::new(base_eta.z, base_k.z)),
+            );
+        }
+
+        let channel = |wavelengths: &[f64; 4], eta: f64, k: f64| {
+            wavelengths
+                .iter()
+                .map(|wavelength| self.airy(cos_theta, *wavelength, Complex::new(eta, k)))
+                .sum::<f64>()
+                / wavelengths.len() as f64
+        };
+
+        Vector::new(
+            channel(&WAVELENGTHS[0], base_eta.x, base_k.x),
+            channel(&WAVELENGTHS[1], base_eta.y, base_k.y),
+            channel(&WAVELENGTHS[2], base_eta.z, base_k.z),
+        )
+    }
+
+    // Reflectance at one wavelength from the sum of all the waves bouncing inside the film
+    fn airy(&self, cos_theta: f64, wavelength: f64, base: Complex) -> f64 {
+        let one = Complex::new(1.0, 0.0);
+        let n1 = Complex::new(self.eta, 0.0);
+        let cos0 = Complex::new(cos_theta.clamp(0.0, 1.0), 0.0);
+        let sin2 = Complex::new(1.0 - cos0.re * cos0.re, 0.0);
+
+        // Snell's law into the film and into the base
+        let cos1 = (one - sin2 / (n1 * n1)).sqrt();
+        let cos2 = (one - sin2 / (base * base)).sqrt();
+
+        // Amplitudes reflected at the top and the bottom of the film, for both polarizations
+        let r01_s = (cos0 - n1 * cos1) / (cos0 + n1 * cos1);
+        let r01_p = (n1 * cos0 - cos1) / (n1 * cos0 + cos1);
+        let r12_s = (n1 * cos1 - base * cos2) / (n1 * cos1 + base * cos2);
+        let r12_p = (base * cos1 - n1 * cos2) / (base * cos1 + n1 * cos2);
+
+        // Phase difference of a trip down and up the film
+        let phase = (n1 * cos1 * Complex::new(4.0 * PI * self.thickness / wavelength, 0.0)).exp_i();
+
+        let r_s = (r01_s + r12_s * phase) / (one + r01_s * r12_s * phase);
+        let r_p = (r01_p + r12_p * phase) / (one + r01_p * r12_p * phase);
+
+        ((r_s.norm_squared() + r_p.norm_squared()) / 2.0).clamp(0.0, 1.0)
+    }
+}
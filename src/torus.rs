@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    aabb::{disk_extent, Aabb},
+    csg::Solid,
+    init_panic_hook,
+    intersection::{angle_tangent, Intersectable},
+    material::Material,
+    ray::Ray,
+    solver::{solve_quartic, Roots},
+    vector::Vector,
+};
+
+// Rust Torus struct, a ring around the axis through its center
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Torus {
+    pub center: Vector,
+    pub axis: Vector,
+    pub major_radius: f64, // Distance from the center to the middle of the tube
+    pub minor_radius: f64, // Radius of the tube
+    pub material: Material,
+}
+
+impl Torus {
+    // Method to bring a point into the space where the axis of the torus is the y-axis
+    fn world_to_local(&self, vector: &Vector) -> Vector {
+        let axis = self.axis.normalize();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+
+        Vector::new(
+            vector.dot(&tangent),
+            vector.dot(&axis),
+            vector.dot(&bitangent),
+        )
+    }
+
+    fn local_to_world(&self, vector: &Vector) -> Vector {
+        let axis = self.axis.normalize();
+        let (tangent, bitangent) = axis.orthonormal_basis();
+
+        tangent * vector.x + axis * vector.y + bitangent * vector.z
+    }
+}
+
+impl Intersectable for Torus {
+    fn intersect(&self, ray: &Ray) -> Option<f64> {
+        self.crossings(ray)
+            .iter()
+            .copied()
+            .find(|t| ray.contains(*t))
+    }
+
+    fn calculate_normal(&self, point: &Vector) -> Vector {
+        // Direction from the closest point on the middle of the tube
+        let local = self.world_to_local(&(*point - self.center));
+        let ring = Vector::new(local.x, 0.0, local.z).normalize() * self.major_radius;
+
+        self.local_to_world(&(local - ring)).normalize()
+    }
+
+    fn calculate_uv(&self, point: &Vector) -> (f64, f64) {
+        // u goes around the axis and v around the tube
+        let local = self.world_to_local(&(*point - self.center));
+        let distance = (local.x * local.x + local.z * local.z).sqrt();
+
+        let u = 0.5 + local.z.atan2(local.x) / (2.0 * PI);
+        let v = 0.5 + local.y.atan2(distance - self.major_radius) / (2.0 * PI);
+
+        (u, v)
+    }
+
+    fn calculate_tangents(&self, point: &Vector) -> (Vector, Vector) {
+        let offset = *point - self.center;
+        let local = self.world_to_local(&offset);
+        let distance = (local.x * local.x + local.z * local.z).sqrt();
+
+        // Around the tube, from the outside of the ring towards the axis
+        let ring = self
+            .local_to_world(&Vector::new(local.x, 0.0, local.z))
+            .normalize();
+        let axis = self.axis.normalize();
+        let dpdv = (axis * (distance - self.major_radius) - ring * local.y) * (2.0 * PI);
+
+        (angle_tangent(&axis, &offset), dpdv)
+    }
+
+    fn bounds(&self) -> Aabb {
+        // The torus is the ring in the middle of the tube grown by the radius of the tube
+        let ring = disk_extent(&self.axis, self.major_radius);
+        let tube = Vector::new(self.minor_radius, self.minor_radius, self.minor_radius);
+
+        Aabb::from_center(self.center, ring + tube)
+    }
+
+    fn get_material(&self) -> &Material {
+        &self.material
+    }
+}
+
+impl Solid for Torus {
+    fn crossings(&self, ray: &Ray) -> Roots {
+        // Solve in local space with a normalized direction to keep the quartic well conditioned
+        let scale = ray.direction.magnitude();
+        let origin = self.world_to_local(&(ray.origin - self.center));
+        let direction = self.world_to_local(&ray.direction) / scale;
+
+        let major_sqrd = self.major_radius * self.major_radius;
+        let minor_sqrd = self.minor_radius * self.minor_radius;
+        let e = origin.dot(&origin) - major_sqrd - minor_sqrd;
+        let f = origin.dot(&direction);
+        let four_major_sqrd = 4.0 * major_sqrd;
+
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f + four_major_sqrd * direction.y * direction.y,
+            4.0 * f * e + 2.0 * four_major_sqrd * origin.y * direction.y,
+            e * e - four_major_sqrd * (minor_sqrd - origin.y * origin.y),
+        );
+
+        roots.map(|t| t / scale)
+    }
+}
+
+#[wasm_bindgen]
+impl Torus {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        center: Vector,
+        axis: Vector,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Material,
+    ) -> Torus {
+        init_panic_hook();
+
+        Torus {
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Vector::default(),
+            Vector::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Material::new(Vector::new(1.0, 1.0, 1.0), 1.0, Vector::default(), 0.0),
+        )
+    }
+
+    #[test]
+    fn ray_crosses_both_sides_of_the_tube() {
+        let torus = torus();
+        let ray = Ray::new(Vector::new(-5.0, 0.0, 0.0), Vector::new(2.0, 0.0, 0.0));
+
+        // The direction is twice as long, so t is half the distance
+        let crossings = torus.crossings(&ray);
+        let expected = [1.25, 1.75, 3.25, 3.75];
+        assert_eq!(crossings.len(), expected.len());
+        for (t, expected_t) in crossings.iter().zip(expected) {
+            assert!((t - expected_t).abs() < 1e-9, "{crossings:?}");
+        }
+
+        let t = torus.intersect(&ray).unwrap();
+        let intersection = torus.intersection(&ray, t);
+        assert!((intersection.intersection_point - Vector::new(-2.5, 0.0, 0.0)).magnitude() < 1e-9);
+        assert!((intersection.geometric_normal - Vector::new(-1.0, 0.0, 0.0)).magnitude() < 1e-9);
+        assert!(intersection.front_face);
+    }
+
+    #[test]
+    fn ray_along_the_axis_misses() {
+        let ray = Ray::new(Vector::new(0.0, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+
+        assert!(torus().intersect(&ray).is_none());
+    }
+}
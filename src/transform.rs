@@ -0,0 +1,373 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{init_panic_hook, ray::Ray, vector::Vector};
+
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+// Rust Transform struct, an affine 4x4 matrix together with its inverse
+#[wasm_bindgen]
+#[derive(Debug, Copy, Clone)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Transform {
+    #[wasm_bindgen(constructor)]
+    pub fn identity() -> Transform {
+        init_panic_hook();
+
+        Transform::default()
+    }
+
+    pub fn translate(offset: Vector) -> Transform {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for (i, value) in [offset.x, offset.y, offset.z].into_iter().enumerate() {
+            matrix[i][3] = value;
+            inverse[i][3] = -value;
+        }
+
+        Transform { matrix, inverse }
+    }
+
+    // Scale along each axis, none of the factors can be zero
+    pub fn scale(factors: Vector) -> Result<Transform, JsValue> {
+        let values = [factors.x, factors.y, factors.z];
+        if values
+            .iter()
+            .any(|factor| *factor == 0.0 || !factor.is_finite())
+        {
+            return Err(JsValue::from_str(
+                "A scale factor has to be a finite number other than zero",
+            ));
+        }
+
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for (i, value) in values.into_iter().enumerate() {
+            matrix[i][i] = value;
+            inverse[i][i] = 1.0 / value;
+        }
+
+        Ok(Transform { matrix, inverse })
+    }
+
+    // Rotate counterclockwise around an axis, the angle is in degrees
+    pub fn rotate(axis: Vector, angle: f64) -> Result<Transform, JsValue> {
+        let quaternion = axis_angle_quaternion(&axis, angle).ok_or_else(|| {
+            JsValue::from_str("A rotation needs an axis with a length and a finite angle")
+        })?;
+
+        Ok(Transform::from_unit_quaternion(quaternion))
+    }
+
+    pub fn rotate_x(angle: f64) -> Result<Transform, JsValue> {
+        Transform::rotate(Vector::new(1.0, 0.0, 0.0), angle)
+    }
+
+    pub fn rotate_y(angle: f64) -> Result<Transform, JsValue> {
+        Transform::rotate(Vector::new(0.0, 1.0, 0.0), angle)
+    }
+
+    pub fn rotate_z(angle: f64) -> Result<Transform, JsValue> {
+        Transform::rotate(Vector::new(0.0, 0.0, 1.0), angle)
+    }
+
+    // Rotation from a quaternion, it's normalized first so it does not have to be a unit quaternion
+    pub fn from_quaternion(x: f64, y: f64, z: f64, w: f64) -> Result<Transform, JsValue> {
+        let quaternion = unit_quaternion([x, y, z, w]).ok_or_else(|| {
+            JsValue::from_str("A quaternion has to be finite numbers that are not all zero")
+        })?;
+
+        Ok(Transform::from_unit_quaternion(quaternion))
+    }
+
+    // Transform that first scales, then rotates by the quaternion and then translates
+    pub fn from_trs(
+        translation: Vector,
+        rotation_x: f64,
+        rotation_y: f64,
+        rotation_z: f64,
+        rotation_w: f64,
+        scale: Vector,
+    ) -> Result<Transform, JsValue> {
+        Ok(Transform::scale(scale)?
+            .then(&Transform::from_quaternion(
+                rotation_x, rotation_y, rotation_z, rotation_w,
+            )?)
+            .then(&Transform::translate(translation)))
+    }
+
+    // Transform that applies this transform first and the other one after it
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            matrix: multiply(&other.matrix, &self.matrix),
+            inverse: multiply(&self.inverse, &other.inverse),
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn transform_point(&self, point: &Vector) -> Vector {
+        apply(&self.matrix, point, 1.0)
+    }
+
+    pub fn transform_vector(&self, vector: &Vector) -> Vector {
+        apply(&self.matrix, vector, 0.0)
+    }
+
+    // Normals are transformed by the inverse transpose so they stay perpendicular to the surface
+    pub fn transform_normal(&self, normal: &Vector) -> Vector {
+        let m = &self.inverse;
+        Vector {
+            x: m[0][0] * normal.x + m[1][0] * normal.y + m[2][0] * normal.z,
+            y: m[0][1] * normal.x + m[1][1] * normal.y + m[2][1] * normal.z,
+            z: m[0][2] * normal.x + m[1][2] * normal.y + m[2][2] * normal.z,
+        }
+    }
+
+    pub fn translation(&self) -> Vector {
+        Vector::new(self.matrix[0][3], self.matrix[1][3], self.matrix[2][3])
+    }
+
+    // Same transform but moved so the origin ends up at the given point
+    pub fn with_translation(&self, translation: Vector) -> Transform {
+        self.then(&Transform::translate(translation - self.translation()))
+    }
+}
+
+impl Transform {
+    fn from_unit_quaternion([x, y, z, w]: [f64; 4]) -> Transform {
+        let rotation = [
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ];
+
+        // The inverse of a rotation is its transpose
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix[i][j] = rotation[i][j];
+                inverse[j][i] = rotation[i][j];
+            }
+        }
+
+        Transform { matrix, inverse }
+    }
+
+    // Method to bring a ray into the space before the transform, the parameter t stays the same
+    pub fn inverse_transform_ray(&self, ray: &Ray) -> Ray {
+        // The direction is not normalized so the interval of the ray stays the same
+        Ray {
+            origin: apply(&self.inverse, &ray.origin, 1.0),
+            direction: apply(&self.inverse, &ray.direction, 0.0),
+            ..*ray
+        }
+    }
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+
+    result
+}
+
+fn apply(m: &Matrix, v: &Vector, w: f64) -> Vector {
+    Vector {
+        x: m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z + m[0][3] * w,
+        y: m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z + m[1][3] * w,
+        z: m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z + m[2][3] * w,
+    }
+}
+
+// Method to scale a quaternion to unit length, None if it has no length or is not made of finite numbers
+fn unit_quaternion(quaternion: [f64; 4]) -> Option<[f64; 4]> {
+    let length = quaternion
+        .iter()
+        .map(|value| value * value)
+        .sum::<f64>()
+        .sqrt();
+    if length == 0.0 || !length.is_finite() {
+        return None;
+    }
+
+    Some(quaternion.map(|value| value / length))
+}
+
+// Method to get the unit quaternion of a rotation by the angle in degrees, None if the axis has no length
+fn axis_angle_quaternion(axis: &Vector, angle: f64) -> Option<[f64; 4]> {
+    let length = axis.magnitude();
+    if length == 0.0 || !length.is_finite() || !angle.is_finite() {
+        return None;
+    }
+
+    let half_angle = angle.to_radians() / 2.0;
+    let axis = *axis * (half_angle.sin() / length);
+
+    Some([axis.x, axis.y, axis.z, half_angle.cos()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Vector, b: &Vector) {
+        assert!((*a - *b).magnitude() < 1e-9, "expected {b:?} but got {a:?}");
+    }
+
+    fn assert_matrix_close(a: &Matrix, b: &Matrix) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((a[i][j] - b[i][j]).abs() < 1e-9, "{a:?} != {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform() {
+        let transform = Transform::from_trs(
+            Vector::new(1.0, -2.0, 3.0),
+            0.3,
+            -0.5,
+            0.2,
+            0.8,
+            Vector::new(2.0, 0.5, 3.0),
+        )
+        .unwrap();
+
+        assert_matrix_close(&multiply(&transform.matrix, &transform.inverse), &IDENTITY);
+        assert_matrix_close(&transform.then(&transform.inverse()).matrix, &IDENTITY);
+
+        let point = Vector::new(0.5, 4.0, -1.5);
+        assert_close(
+            &transform
+                .inverse()
+                .transform_point(&transform.transform_point(&point)),
+            &point,
+        );
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_to_the_surface() {
+        let transform = Transform::scale(Vector::new(3.0, 1.0, 0.5))
+            .unwrap()
+            .then(&Transform::rotate(Vector::new(1.0, 1.0, 0.0), 30.0).unwrap())
+            .then(&Transform::translate(Vector::new(5.0, 0.0, 0.0)));
+
+        // A tangent of the plane x + y + z = 0 and its normal
+        let tangent = Vector::new(1.0, -1.0, 0.0);
+        let normal = Vector::new(1.0, 1.0, 1.0);
+
+        let tangent = transform.transform_vector(&tangent);
+        let normal = transform.transform_normal(&normal);
+        assert!(tangent.dot(&normal).abs() < 1e-9);
+
+        // Translation does not move normals
+        let moved = Transform::translate(Vector::new(1.0, 2.0, 3.0));
+        assert_close(&moved.transform_normal(&normal), &normal);
+    }
+
+    #[test]
+    fn quaternions_compose() {
+        // Two quarter turns around x are half a turn
+        let half_turn = Transform::rotate_x(90.0)
+            .unwrap()
+            .then(&Transform::rotate_x(90.0).unwrap());
+        assert_matrix_close(
+            &half_turn.matrix,
+            &Transform::rotate_x(180.0).unwrap().matrix,
+        );
+
+        // Composing matches the product of the quaternions, q2 * q1 applies q1 first
+        let (x1, y1, z1, w1) = (0.1, 0.7, -0.2, 0.6);
+        let (x2, y2, z2, w2) = (-0.4, 0.1, 0.5, 0.3);
+        let product = (
+            w2 * x1 + x2 * w1 + y2 * z1 - z2 * y1,
+            w2 * y1 - x2 * z1 + y2 * w1 + z2 * x1,
+            w2 * z1 + x2 * y1 - y2 * x1 + z2 * w1,
+            w2 * w1 - x2 * x1 - y2 * y1 - z2 * z1,
+        );
+        let composed = Transform::from_quaternion(x1, y1, z1, w1)
+            .unwrap()
+            .then(&Transform::from_quaternion(x2, y2, z2, w2).unwrap());
+        let expected =
+            Transform::from_quaternion(product.0, product.1, product.2, product.3).unwrap();
+        assert_matrix_close(&composed.matrix, &expected.matrix);
+        assert_matrix_close(&composed.inverse, &expected.inverse);
+
+        // A quarter turn around z takes x to y
+        assert_close(
+            &Transform::rotate_z(90.0)
+                .unwrap()
+                .transform_vector(&Vector::new(1.0, 0.0, 0.0)),
+            &Vector::new(0.0, 1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn rotations_need_an_axis_and_a_quaternion_with_a_length() {
+        assert!(unit_quaternion([0.0; 4]).is_none());
+        assert!(unit_quaternion([f64::NAN, 0.0, 0.0, 1.0]).is_none());
+        assert!(unit_quaternion([0.0, 0.0, f64::INFINITY, 1.0]).is_none());
+        assert!(axis_angle_quaternion(&Vector::default(), 45.0).is_none());
+        assert!(axis_angle_quaternion(&Vector::new(0.0, f64::NAN, 0.0), 45.0).is_none());
+        assert!(axis_angle_quaternion(&Vector::new(0.0, 1.0, 0.0), f64::INFINITY).is_none());
+
+        // Any length of the axis or the quaternion gives the same rotation
+        let quaternion = unit_quaternion([0.0, 0.0, 3.0, 4.0]).unwrap();
+        assert_matrix_close(
+            &Transform::from_unit_quaternion(quaternion).matrix,
+            &Transform::from_quaternion(0.0, 0.0, 0.6, 0.8)
+                .unwrap()
+                .matrix,
+        );
+        assert_matrix_close(
+            &Transform::rotate(Vector::new(0.0, 5.0, 0.0), 30.0)
+                .unwrap()
+                .matrix,
+            &Transform::rotate_y(30.0).unwrap().matrix,
+        );
+    }
+}
@@ -1,10 +1,11 @@
 use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub, SubAssign};
 
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 // Rust Vector struct
 #[wasm_bindgen]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
 pub struct Vector {
     pub x: f64,
     pub y: f64,
@@ -61,6 +62,38 @@ impl Vector {
     }
 }
 
+impl Vector {
+    // Method to get two unit vectors that together with the vector form an orthonormal basis
+    pub fn orthonormal_basis(&self) -> (Vector, Vector) {
+        // Branchless construction by Duff et al., the vector has to be normalized
+        let sign = 1.0_f64.copysign(self.z);
+        let a = -1.0 / (sign + self.z);
+        let b = self.x * self.y * a;
+
+        let tangent = Vector {
+            x: 1.0 + sign * self.x * self.x * a,
+            y: sign * b,
+            z: -sign * self.x,
+        };
+        let bitangent = Vector {
+            x: b,
+            y: sign + self.y * self.y * a,
+            z: -self.y,
+        };
+
+        (tangent, bitangent)
+    }
+
+    // Method to get the absolute value of every component
+    pub fn abs(&self) -> Vector {
+        Vector {
+            x: self.x.abs(),
+            y: self.y.abs(),
+            z: self.z.abs(),
+        }
+    }
+}
+
 // Method to add another vector
 impl Add for Vector {
     type Output = Self;
@@ -164,3 +197,16 @@ impl Div<f64> for Vector {
         }
     }
 }
+
+// Method to divide by a Vector
+impl Div for Vector {
+    type Output = Self;
+
+    fn div(self, v: Self) -> Self {
+        Self {
+            x: self.x / v.x,
+            y: self.y / v.y,
+            z: self.z / v.z,
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    init_panic_hook, medium::Medium, random::Random, ray::Ray, spectrum::Wavelengths,
+    vector::Vector,
+};
+
+// Handle of a volume in the scene
+pub type VolumeId = u32;
+
+// Rust VoxelGrid struct, values at the centers of the voxels, x changing fastest and then y
+#[derive(Debug)]
+struct VoxelGrid {
+    values: Vec<f32>,
+    dimensions: [usize; 3],
+    max_value: f64,
+}
+
+impl VoxelGrid {
+    fn new(values: Vec<f32>, dimensions: [usize; 3]) -> Result<VoxelGrid, JsValue> {
+        let count = voxel_count(&dimensions)?;
+        if dimensions.contains(&0) || values.len() != count {
+            return Err(JsValue::from_str(
+                "A voxel grid needs one value for every voxel and at least one voxel along every axis",
+            ));
+        }
+        let max_value = values
+            .iter()
+            .fold(0.0_f64, |max, value| max.max(*value as f64));
+
+        Ok(VoxelGrid {
+            values,
+            dimensions,
+            max_value,
+        })
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f64 {
+        let [nx, ny, _] = self.dimensions;
+
+        (self.values[(z * ny + y) * nx + x] as f64).max(0.0)
+    }
+
+    // Method to blend the eight voxels around a point given between 0 and 1 along every axis
+    fn trilinear(&self, point: &Vector) -> f64 {
+        let [nx, ny, nz] = self.dimensions;
+        let axis = |t: f64, n: usize| {
+            let p = (t * n as f64 - 0.5).clamp(0.0, (n - 1) as f64);
+            let i = (p.floor() as usize).min(n.saturating_sub(2));
+            (i, (i + 1).min(n - 1), p - i as f64)
+        };
+        let (x0, x1, fx) = axis(point.x, nx);
+        let (y0, y1, fy) = axis(point.y, ny);
+        let (z0, z1, fz) = axis(point.z, nz);
+
+        let lerp = |a: f64, b: f64, t: f64| a + (b - a) * t;
+        let plane = |z: usize| {
+            lerp(
+                lerp(self.value(x0, y0, z), self.value(x1, y0, z), fx),
+                lerp(self.value(x0, y1, z), self.value(x1, y1, z), fx),
+                fy,
+            )
+        };
+
+        lerp(plane(z0), plane(z1), fz)
+    }
+}
+
+// Method to get the number of voxels in a grid, which can be too many to count from the header of a file
+fn voxel_count(dimensions: &[usize; 3]) -> Result<usize, JsValue> {
+    dimensions
+        .iter()
+        .try_fold(1_usize, |count, dimension| count.checked_mul(*dimension))
+        .ok_or_else(|| JsValue::from_str("A voxel grid has too many voxels"))
+}
+
+// Method to read the raw format, three little-endian u32 dimensions followed by little-endian f32 values
+fn read_raw(bytes: &[u8]) -> Result<VoxelGrid, JsValue> {
+    if bytes.len() < 12 {
+        return Err(JsValue::from_str(
+            "A raw voxel grid starts with its three dimensions",
+        ));
+    }
+    let dimension = |i: usize| {
+        u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]) as usize
+    };
+    let dimensions = [dimension(0), dimension(4), dimension(8)];
+
+    // The values have to fill the rest of the file exactly
+    let expected = voxel_count(&dimensions)?
+        .checked_mul(4)
+        .and_then(|length| length.checked_add(12));
+    if expected != Some(bytes.len()) {
+        return Err(JsValue::from_str(&format!(
+            "A raw voxel grid of {} by {} by {} needs 4 bytes for every voxel after its dimensions",
+            dimensions[0], dimensions[1], dimensions[2]
+        )));
+    }
+
+    let values = bytes[12..]
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+
+    VoxelGrid::new(values, dimensions)
+}
+
+// Rust VolumeEvent enum, what happened to a ray going through the media
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum VolumeEvent {
+    Pass,
+    Scatter { distance: f64, anisotropy: f64 },
+    Absorb,
+}
+
+// Rust VolumeTrack struct, the result of tracking a ray through the media
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct VolumeTrack {
+    pub event: VolumeEvent,
+    pub weight: Vector,  // Multiplies the color of the ray
+    pub emitted: Vector, // Light emitted towards the ray, already weighted
+}
+
+// Rust Volume struct, smoke, clouds or fire with the density of a voxel grid in a box
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Volume {
+    density: Rc<VoxelGrid>,
+    emission: Option<Rc<VoxelGrid>>, // Strength of the emission in every voxel, like the temperature of fire
+    pub origin: Vector,              // Lowest corner of the box
+    pub size: Vector,
+    pub absorption: Vector, // Per unit of distance at a density of 1, for red, green and blue
+    pub scattering: Vector,
+    pub anisotropy: f64, // Henyey-Greenstein g of the scattering
+    pub emission_color: Vector,
+    pub emission_power: f64,
+}
+
+impl Volume {
+    fn new(density: VoxelGrid, origin: Vector, size: Vector) -> Volume {
+        Volume {
+            density: Rc::new(density),
+            emission: None,
+            origin,
+            size,
+            absorption: Vector::new(0.1, 0.1, 0.1),
+            scattering: Vector::new(1.0, 1.0, 1.0),
+            anisotropy: 0.0,
+            emission_color: Vector::new(1.0, 0.5, 0.1),
+            emission_power: 0.0,
+        }
+    }
+
+    // Method to get the volume with its coefficients and emission at the wavelengths of a path in spectral mode
+    pub(crate) fn spectral(&self, wavelengths: &Wavelengths) -> Volume {
+        Volume {
+            absorption: wavelengths.upsample(&self.absorption),
+            scattering: wavelengths.upsample(&self.scattering),
+            emission_color: wavelengths.upsample(&self.emission_color),
+            ..self.clone()
+        }
+    }
+
+    // Method to get where the ray is inside the box, as distances along the normalized direction
+    fn clip(&self, ray: &Ray) -> Option<(f64, f64)> {
+        let direction = ray.direction.normalize();
+        let max = self.origin + self.size;
+        let mut near = 0.0_f64;
+        let mut far = f64::INFINITY;
+
+        for (origin, direction, min, max) in [
+            (ray.origin.x, direction.x, self.origin.x, max.x),
+            (ray.origin.y, direction.y, self.origin.y, max.y),
+            (ray.origin.z, direction.z, self.origin.z, max.z),
+        ] {
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            near = near.max(t1.min(t2));
+            far = far.min(t1.max(t2));
+        }
+
+        (near < far).then_some((near, far))
+    }
+
+    // Method to get the point between 0 and 1 along every axis of the box, None outside of it
+    fn local(&self, point: &Vector) -> Option<Vector> {
+        let offset = *point - self.origin;
+        let local = Vector::new(
+            offset.x / self.size.x,
+            offset.y / self.size.y,
+            offset.z / self.size.z,
+        );
+
+        [local.x, local.y, local.z]
+            .iter()
+            .all(|t| (0.0..=1.0).contains(t))
+            .then_some(local)
+    }
+
+    // Highest extinction in the volume, a bound for the tracking
+    fn majorant(&self) -> f64 {
+        let extinction = self.absorption + self.scattering;
+
+        self.density.max_value * extinction.x.max(extinction.y).max(extinction.z).max(0.0)
+    }
+}
+
+// Method to track a ray through the volumes and the homogeneous medium it is in up to max_distance along its
+// normalized direction, with delta tracking, or with ratio tracking if nothing scatters so that the ray always
+// gets through
+pub(crate) fn track_media(
+    volumes: &[Volume],
+    medium: Option<&Medium>,
+    ray: &Ray,
+    max_distance: f64,
+    random: &mut Random,
+) -> VolumeTrack {
+    let mut track = VolumeTrack {
+        event: VolumeEvent::Pass,
+        weight: Vector::new(1.0, 1.0, 1.0),
+        emitted: Vector::default(),
+    };
+
+    // Part of the ray that goes through any of the volumes along it, with the bound of their extinction
+    // and whether any of them scatters
+    let scatters =
+        |scattering: &Vector| scattering.x > 0.0 || scattering.y > 0.0 || scattering.z > 0.0;
+    let mut crossed = None;
+    let mut volumes_majorant = 0.0;
+    let mut volumes_scatter = false;
+    for volume in volumes {
+        let Some((near, far)) = volume.clip(ray) else {
+            continue;
+        };
+        let majorant = volume.majorant();
+        if near >= max_distance || majorant <= 0.0 {
+            continue;
+        }
+
+        let (start, end) = crossed.unwrap_or((near, far));
+        crossed = Some((start.min(near), end.max(far)));
+        volumes_majorant += majorant;
+        volumes_scatter |= scatters(&volume.scattering);
+    }
+    let Some((start, far)) = crossed else {
+        // Without volumes the distance in the medium is sampled exactly
+        if let Some(medium) = medium {
+            let (distance, weight) = medium.sample_distance(max_distance, random);
+            track.weight = weight;
+            if let Some(distance) = distance {
+                track.event = VolumeEvent::Scatter {
+                    distance,
+                    anisotropy: medium.anisotropy,
+                };
+            }
+        }
+        return track;
+    };
+
+    // The medium is everywhere up to max_distance and adds to the bound of the extinction
+    let medium_extinction = medium.map_or(Vector::default(), Medium::extinction);
+    let (start, end) = if medium.is_some() {
+        (0.0, max_distance)
+    } else {
+        (start, far.min(max_distance))
+    };
+    let majorant = volumes_majorant
+        + medium_extinction
+            .x
+            .max(medium_extinction.y)
+            .max(medium_extinction.z)
+            .max(0.0);
+    let ratio_tracking =
+        !volumes_scatter && !medium.is_some_and(|medium| scatters(&medium.scattering));
+
+    let direction = ray.direction.normalize();
+    let mut distance = start;
+    loop {
+        distance -= (1.0 - random.random_value()).max(f64::MIN_POSITIVE).ln() / majorant;
+        if distance >= end {
+            return track;
+        }
+
+        // Coefficients of all the volumes at the point
+        let point = ray.origin + direction * distance;
+        let mut absorption = Vector::default();
+        let mut scattering = Vector::default();
+        let mut emission = Vector::default();
+        let mut anisotropy = 0.0;
+        if let Some(medium) = medium {
+            anisotropy += medium.anisotropy
+                * (medium.scattering.x + medium.scattering.y + medium.scattering.z);
+            absorption += medium.absorption;
+            scattering += medium.scattering;
+        }
+        // Only the volumes that count for the majorant, a point between start and end is only inside
+        // the box of a volume that the ray crosses before max_distance
+        for volume in volumes.iter().filter(|volume| volume.majorant() > 0.0) {
+            let Some(local) = volume.local(&point) else {
+                continue;
+            };
+            let density = volume.density.trilinear(&local);
+            let volume_absorption = volume.absorption * density;
+            let volume_scattering = volume.scattering * density;
+
+            if let Some(grid) = &volume.emission {
+                emission += volume_absorption
+                    * volume.emission_color
+                    * (volume.emission_power * grid.trilinear(&local));
+            }
+            anisotropy += volume.anisotropy
+                * (volume_scattering.x + volume_scattering.y + volume_scattering.z);
+            absorption += volume_absorption;
+            scattering += volume_scattering;
+        }
+        let null = Vector::new(majorant, majorant, majorant) - absorption - scattering;
+
+        // Light emitted at the collision, then what happens to the ray there
+        track.emitted += track.weight * emission / majorant;
+
+        if ratio_tracking {
+            track.weight *= null / majorant;
+            continue;
+        }
+
+        let average = |v: Vector| (v.x + v.y + v.z) / 3.0;
+        let absorb_probability = average(absorption) / majorant;
+        let scatter_probability = average(scattering) / majorant;
+        let null_probability = (1.0 - absorb_probability - scatter_probability).max(0.0);
+
+        let choice = random.random_value();
+        if choice < absorb_probability {
+            track.event = VolumeEvent::Absorb;
+            return track;
+        } else if choice < absorb_probability + scatter_probability {
+            let total = scattering.x + scattering.y + scattering.z;
+            track.weight *= scattering / (majorant * scatter_probability);
+            track.event = VolumeEvent::Scatter {
+                distance,
+                anisotropy: if total > 0.0 { anisotropy / total } else { 0.0 },
+            };
+            return track;
+        } else if null_probability > 0.0 {
+            track.weight *= null / (majorant * null_probability);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Volume {
+    // Densities with x changing fastest and then y, in a box from the origin
+    pub fn from_densities(
+        densities: Vec<f32>,
+        x: u32,
+        y: u32,
+        z: u32,
+        origin: Vector,
+        size: Vector,
+    ) -> Result<Volume, JsValue> {
+        init_panic_hook();
+
+        let grid = VoxelGrid::new(densities, [x as usize, y as usize, z as usize])?;
+
+        Ok(Volume::new(grid, origin, size))
+    }
+
+    // Raw file of three little-endian u32 dimensions followed by the little-endian f32 densities
+    pub fn from_bytes(bytes: &[u8], origin: Vector, size: Vector) -> Result<Volume, JsValue> {
+        init_panic_hook();
+
+        Ok(Volume::new(read_raw(bytes)?, origin, size))
+    }
+
+    // Strength of the emission in every voxel, with the same dimensions as the densities
+    pub fn with_emission(&self, values: Vec<f32>) -> Result<Volume, JsValue> {
+        let grid = VoxelGrid::new(values, self.density.dimensions)?;
+
+        Ok(Volume {
+            emission: Some(Rc::new(grid)),
+            ..self.clone()
+        })
+    }
+
+    // Same as with_emission from a raw file, which has to have the same dimensions as the densities
+    pub fn with_emission_bytes(&self, bytes: &[u8]) -> Result<Volume, JsValue> {
+        let grid = read_raw(bytes)?;
+        if grid.dimensions != self.density.dimensions {
+            return Err(JsValue::from_str(
+                "The emission needs the same dimensions as the densities",
+            ));
+        }
+
+        Ok(Volume {
+            emission: Some(Rc::new(grid)),
+            ..self.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Box from -1 to 1 along every axis with the same density everywhere and nothing that scatters
+    fn constant_volume(density: f32) -> Volume {
+        let grid = VoxelGrid::new(vec![density; 8], [2, 2, 2]).unwrap();
+
+        Volume {
+            absorption: Vector::new(0.2, 0.5, 1.0),
+            scattering: Vector::default(),
+            ..Volume::new(
+                grid,
+                Vector::new(-1.0, -1.0, -1.0),
+                Vector::new(2.0, 2.0, 2.0),
+            )
+        }
+    }
+
+    // Mean weight of the ray, which estimates the transmittance through the volume
+    fn mean_weight(volume: &Volume, ray: &Ray, max_distance: f64) -> Vector {
+        let count = 100_000;
+        let mut random = Random::new(9);
+        let mut total = Vector::default();
+        for _ in 0..count {
+            let track = track_media(
+                std::slice::from_ref(volume),
+                None,
+                ray,
+                max_distance,
+                &mut random,
+            );
+            assert!(matches!(track.event, VolumeEvent::Pass));
+            total += track.weight;
+        }
+
+        total / count as f64
+    }
+
+    fn assert_beer_lambert(estimate: Vector, extinction: Vector, length: f64) {
+        for (estimate, extinction) in [
+            (estimate.x, extinction.x),
+            (estimate.y, extinction.y),
+            (estimate.z, extinction.z),
+        ] {
+            let expected = (-extinction * length).exp();
+            assert!(
+                (estimate - expected).abs() < 0.01 * expected.max(0.1),
+                "expected {expected} but got {estimate}"
+            );
+        }
+    }
+
+    #[test]
+    fn ratio_tracking_through_a_constant_density() {
+        let volume = constant_volume(1.5);
+        let extinction = volume.absorption * 1.5;
+
+        // All the way through the box
+        let ray = Ray::new(Vector::new(-5.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0));
+        assert_beer_lambert(mean_weight(&volume, &ray, f64::INFINITY), extinction, 2.0);
+
+        // Stopped by a surface halfway through, the length of the direction does not matter
+        let ray = Ray::new(Vector::new(-5.0, 0.0, 0.0), Vector::new(3.0, 0.0, 0.0));
+        assert_beer_lambert(mean_weight(&volume, &ray, 5.0), extinction, 1.0);
+
+        // Starting inside the box
+        let ray = Ray::new(Vector::new(0.5, 0.0, 0.0), Vector::new(0.0, 0.0, -1.0));
+        assert_beer_lambert(mean_weight(&volume, &ray, f64::INFINITY), extinction, 1.0);
+    }
+}